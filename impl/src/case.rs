@@ -0,0 +1,92 @@
+use proc_macro2::{Ident, Span};
+
+// Rename rules for default tag names, with serde's vocabulary. The order of the
+// variants matches typetag::__private::RenameRule.
+#[derive(Copy, Clone)]
+pub enum RenameRule {
+    None,
+    LowerCase,
+    UpperCase,
+    PascalCase,
+    CamelCase,
+    SnakeCase,
+    ScreamingSnakeCase,
+    KebabCase,
+    ScreamingKebabCase,
+}
+
+use self::RenameRule::{
+    CamelCase, KebabCase, LowerCase, None, PascalCase, ScreamingKebabCase, ScreamingSnakeCase,
+    SnakeCase, UpperCase,
+};
+
+pub static RULES: &[(&str, RenameRule)] = &[
+    ("lowercase", LowerCase),
+    ("UPPERCASE", UpperCase),
+    ("PascalCase", PascalCase),
+    ("camelCase", CamelCase),
+    ("snake_case", SnakeCase),
+    ("SCREAMING_SNAKE_CASE", ScreamingSnakeCase),
+    ("kebab-case", KebabCase),
+    ("SCREAMING-KEBAB-CASE", ScreamingKebabCase),
+];
+
+impl RenameRule {
+    pub const ALL: [RenameRule; 9] = [
+        None,
+        LowerCase,
+        UpperCase,
+        PascalCase,
+        CamelCase,
+        SnakeCase,
+        ScreamingSnakeCase,
+        KebabCase,
+        ScreamingKebabCase,
+    ];
+
+    pub fn from_str(rename_all_str: &str) -> Option<Self> {
+        RULES
+            .iter()
+            .find(|(name, _rule)| *name == rename_all_str)
+            .map(|(_name, rule)| *rule)
+    }
+
+    pub fn ident(self) -> Ident {
+        let name = match self {
+            None => "None",
+            LowerCase => "LowerCase",
+            UpperCase => "UpperCase",
+            PascalCase => "PascalCase",
+            CamelCase => "CamelCase",
+            SnakeCase => "SnakeCase",
+            ScreamingSnakeCase => "ScreamingSnakeCase",
+            KebabCase => "KebabCase",
+            ScreamingKebabCase => "ScreamingKebabCase",
+        };
+        Ident::new(name, Span::call_site())
+    }
+
+    // Type names are PascalCase, so this is the conversion serde applies to
+    // enum variants.
+    pub fn apply(self, type_name: &str) -> String {
+        match self {
+            None | PascalCase => type_name.to_owned(),
+            LowerCase => type_name.to_ascii_lowercase(),
+            UpperCase => type_name.to_ascii_uppercase(),
+            CamelCase => type_name[..1].to_ascii_lowercase() + &type_name[1..],
+            SnakeCase => {
+                let mut snake = String::new();
+                for (i, ch) in type_name.char_indices() {
+                    if i > 0 && ch.is_uppercase() {
+                        snake.push('_');
+                    }
+                    snake.push(ch.to_ascii_lowercase());
+                }
+                snake
+            }
+            ScreamingSnakeCase => SnakeCase.apply(type_name).to_ascii_uppercase(),
+            KebabCase => SnakeCase.apply(type_name).replace('_', "-"),
+            ScreamingKebabCase => ScreamingSnakeCase.apply(type_name).replace('_', "-"),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use proc_macro2::Ident;
+use quote::ToTokens;
+use std::collections::BTreeMap;
+use syn::visit_mut::{self, VisitMut};
+use syn::{Error, GenericArgument, GenericParam, Generics, Path, PathArguments, Result, Type};
+
+pub type Bindings = BTreeMap<Ident, GenericArgument>;
+
+// Match the self type of a generic impl, like `Struct<T>`, against one of its
+// instantiations, like `Struct<u8>`, to find what each impl parameter stands
+// for.
+pub fn unify(generics: &Generics, pattern: &Type, actual: &Type) -> Result<Bindings> {
+    let params: Vec<&Ident> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(param) => Some(&param.ident),
+            GenericParam::Const(param) => Some(&param.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+
+    let mut bindings = Bindings::new();
+    if !unify_type(&params, pattern, actual, &mut bindings) {
+        let msg = "instantiation does not match the self type of this impl";
+        return Err(Error::new_spanned(actual, msg));
+    }
+
+    for param in params {
+        if !bindings.contains_key(param) {
+            let msg = format!("instantiation does not determine `{param}`");
+            return Err(Error::new_spanned(actual, msg));
+        }
+    }
+
+    Ok(bindings)
+}
+
+// Replace impl parameters by what they were bound to, for example to turn the
+// trait path `Trait<T>` into `Trait<u8>`.
+pub fn substitute(path: &mut Path, bindings: &Bindings) {
+    Substitute { bindings }.visit_path_mut(path);
+}
+
+struct Substitute<'a> {
+    bindings: &'a Bindings,
+}
+
+impl Substitute<'_> {
+    fn lookup(&self, ty: &Type) -> Option<&GenericArgument> {
+        match ty {
+            Type::Path(ty) if ty.qself.is_none() => self.bindings.get(ty.path.get_ident()?),
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for Substitute<'_> {
+    fn visit_generic_argument_mut(&mut self, arg: &mut GenericArgument) {
+        if let GenericArgument::Type(ty) = arg {
+            if let Some(bound) = self.lookup(ty) {
+                *arg = bound.clone();
+                return;
+            }
+        }
+        visit_mut::visit_generic_argument_mut(self, arg);
+    }
+
+    fn visit_type_mut(&mut self, ty: &mut Type) {
+        if let Some(GenericArgument::Type(bound)) = self.lookup(ty) {
+            *ty = bound.clone();
+            return;
+        }
+        visit_mut::visit_type_mut(self, ty);
+    }
+}
+
+fn param<'a>(params: &[&'a Ident], ty: &Type) -> Option<&'a Ident> {
+    match ty {
+        Type::Path(ty) if ty.qself.is_none() => {
+            let ident = ty.path.get_ident()?;
+            params.iter().copied().find(|param| *param == ident)
+        }
+        _ => None,
+    }
+}
+
+fn bind(bindings: &mut Bindings, param: &Ident, actual: GenericArgument) -> bool {
+    match bindings.get(param) {
+        Some(existing) => same_tokens(existing, &actual),
+        None => {
+            bindings.insert(param.clone(), actual);
+            true
+        }
+    }
+}
+
+fn unify_type(params: &[&Ident], pattern: &Type, actual: &Type, bindings: &mut Bindings) -> bool {
+    if let Some(param) = param(params, pattern) {
+        return bind(bindings, param, GenericArgument::Type(actual.clone()));
+    }
+
+    match (pattern, actual) {
+        (Type::Group(pattern), _) => unify_type(params, &pattern.elem, actual, bindings),
+        (Type::Paren(pattern), _) => unify_type(params, &pattern.elem, actual, bindings),
+        (_, Type::Group(actual)) => unify_type(params, pattern, &actual.elem, bindings),
+        (_, Type::Paren(actual)) => unify_type(params, pattern, &actual.elem, bindings),
+        (Type::Path(pattern), Type::Path(actual)) => {
+            if pattern.qself.is_some()
+                || actual.qself.is_some()
+                || pattern.path.segments.len() != actual.path.segments.len()
+            {
+                return same_tokens(pattern, actual);
+            }
+            pattern
+                .path
+                .segments
+                .iter()
+                .zip(&actual.path.segments)
+                .all(|(pattern, actual)| {
+                    pattern.ident == actual.ident
+                        && unify_arguments(params, &pattern.arguments, &actual.arguments, bindings)
+                })
+        }
+        (Type::Reference(pattern), Type::Reference(actual)) => {
+            pattern.mutability.is_some() == actual.mutability.is_some()
+                && unify_type(params, &pattern.elem, &actual.elem, bindings)
+        }
+        (Type::Ptr(pattern), Type::Ptr(actual)) => {
+            pattern.mutability.is_some() == actual.mutability.is_some()
+                && unify_type(params, &pattern.elem, &actual.elem, bindings)
+        }
+        (Type::Slice(pattern), Type::Slice(actual)) => {
+            unify_type(params, &pattern.elem, &actual.elem, bindings)
+        }
+        (Type::Array(pattern), Type::Array(actual)) => {
+            unify_type(params, &pattern.elem, &actual.elem, bindings)
+                && same_tokens(&pattern.len, &actual.len)
+        }
+        (Type::Tuple(pattern), Type::Tuple(actual)) => {
+            pattern.elems.len() == actual.elems.len()
+                && pattern
+                    .elems
+                    .iter()
+                    .zip(&actual.elems)
+                    .all(|(pattern, actual)| unify_type(params, pattern, actual, bindings))
+        }
+        _ => same_tokens(pattern, actual),
+    }
+}
+
+fn unify_arguments(
+    params: &[&Ident],
+    pattern: &PathArguments,
+    actual: &PathArguments,
+    bindings: &mut Bindings,
+) -> bool {
+    match (pattern, actual) {
+        (PathArguments::None, PathArguments::None) => true,
+        (PathArguments::AngleBracketed(pattern), PathArguments::AngleBracketed(actual)) => {
+            pattern.args.len() == actual.args.len()
+                && pattern
+                    .args
+                    .iter()
+                    .zip(&actual.args)
+                    .all(|(pattern, actual)| unify_argument(params, pattern, actual, bindings))
+        }
+        _ => same_tokens(pattern, actual),
+    }
+}
+
+fn unify_argument(
+    params: &[&Ident],
+    pattern: &GenericArgument,
+    actual: &GenericArgument,
+    bindings: &mut Bindings,
+) -> bool {
+    // A bare parameter matches any argument, which also covers const generics
+    // since `N` in `Struct<N>` parses as a type.
+    if let GenericArgument::Type(ty) = pattern {
+        if let Some(param) = param(params, ty) {
+            return bind(bindings, param, actual.clone());
+        }
+    }
+
+    match (pattern, actual) {
+        (GenericArgument::Type(pattern), GenericArgument::Type(actual)) => {
+            unify_type(params, pattern, actual, bindings)
+        }
+        (GenericArgument::AssocType(pattern), GenericArgument::AssocType(actual)) => {
+            pattern.ident == actual.ident && unify_type(params, &pattern.ty, &actual.ty, bindings)
+        }
+        _ => same_tokens(pattern, actual),
+    }
+}
+
+fn same_tokens(a: &dyn ToTokens, b: &dyn ToTokens) -> bool {
+    a.to_token_stream().to_string() == b.to_token_stream().to_string()
+}
@@ -5,6 +5,8 @@
     clippy::too_many_lines
 )]
 
+mod case;
+mod instantiate;
 mod parse;
 mod tagged_impl;
 mod tagged_trait;
@@ -1,22 +1,83 @@
+use crate::case::{RenameRule, RULES};
 use quote::quote;
 use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
 use syn::{
-    Attribute, Error, Generics, ImplItem, ItemImpl, ItemTrait, LitStr, Token, TraitItem, Type,
-    TypeParamBound, Visibility, WherePredicate,
+    parenthesized, Attribute, Error, Generics, Ident, ItemImpl, ItemTrait, LitInt, LitStr, Path,
+    Token, TraitItem, Type, TypeParamBound, Visibility, WherePredicate,
 };
 
 mod kw {
+    syn::custom_keyword!(alias);
+    syn::custom_keyword!(borrow);
     syn::custom_keyword!(tag);
     syn::custom_keyword!(content);
     syn::custom_keyword!(default_variant);
+    syn::custom_keyword!(derive);
+    syn::custom_keyword!(id);
     syn::custom_keyword!(deny_unknown_fields);
+    syn::custom_keyword!(instantiate);
     syn::custom_keyword!(name);
+    syn::custom_keyword!(name_strategy);
+    syn::custom_keyword!(rename_all);
+    syn::custom_keyword!(schema);
+    syn::custom_keyword!(seed);
+    syn::custom_keyword!(tag_kind);
+    syn::custom_keyword!(unknown);
+    syn::custom_keyword!(untagged);
+    syn::custom_keyword!(value_key);
+    syn::custom_keyword!(variant);
 }
 
-pub enum TraitArgs {
+pub struct TraitArgs {
+    pub repr: Repr,
+    pub rename_all: RenameRule,
+    pub name_strategy: NameStrategy,
+    pub tag_kind: TagKind,
+    pub unknown: Option<LitStr>,
+    pub seed: Option<Type>,
+    pub borrow: bool,
+    pub instantiate: Vec<Path>,
+    pub derives: Vec<Derive>,
+    pub schema: bool,
+}
+
+// Std traits implemented for the trait object from the tag and serialized
+// content of the impl.
+#[derive(Copy, Clone, PartialEq)]
+pub enum Derive {
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum TagKind {
+    // Impls are tagged by name.
+    Name,
+    // Impls are tagged by their numeric id in formats that are not human
+    // readable, and by name otherwise.
+    Id,
+}
+
+#[derive(Copy, Clone)]
+pub enum NameStrategy {
+    // The type name of the impl, like `Click`.
+    Type,
+    // The type name prefixed by the module path of the impl, like
+    // `my_crate::events::Click`.
+    Path,
+}
+
+pub enum Repr {
     External,
+    Untagged,
+    // Serde's enum representation, with the impl ids as variant indices.
+    Variant,
     Internal {
         tag: LitStr,
+        value_key: Option<LitStr>,
         default_variant: Option<LitStr>,
     },
     Adjacent {
@@ -29,6 +90,15 @@ pub enum TraitArgs {
 
 pub struct ImplArgs {
     pub name: Option<LitStr>,
+    pub aliases: Vec<LitStr>,
+    pub is_override: bool,
+    pub id: Option<LitInt>,
+    pub instantiate: Vec<Instantiation>,
+}
+
+pub struct Instantiation {
+    pub ty: Type,
+    pub name: LitStr,
 }
 
 pub enum Input {
@@ -48,17 +118,20 @@ impl Parse for Input {
             let mut item: ItemTrait = input.parse()?;
             for assoc in &item.items {
                 if let TraitItem::Const(assoc) = assoc {
-                    let const_token = assoc.const_token;
-                    let semi_token = assoc.semi_token;
-                    let span = quote!(#const_token #semi_token);
-                    let msg = "typetag trait with associated const is not supported yet";
-                    return Err(Error::new_spanned(span, msg));
-                } else if let TraitItem::Type(assoc) = assoc {
                     if !is_self_sized(&assoc.generics) {
+                        let const_token = assoc.const_token;
+                        let semi_token = assoc.semi_token;
+                        let span = quote!(#const_token #semi_token);
+                        let msg = "typetag trait with associated const requires \
+                                   `where Self: Sized` to keep the trait object safe";
+                        return Err(Error::new_spanned(span, msg));
+                    }
+                } else if let TraitItem::Type(assoc) = assoc {
+                    if !assoc.generics.params.is_empty() && !is_self_sized(&assoc.generics) {
                         let type_token = assoc.type_token;
                         let semi_token = assoc.semi_token;
                         let span = quote!(#type_token #semi_token);
-                        let msg = "typetag trait with associated type is not supported yet";
+                        let msg = "typetag trait with generic associated type is not supported";
                         return Err(Error::new_spanned(span, msg));
                     }
                 }
@@ -75,15 +148,6 @@ impl Parse for Input {
                 let msg = "expected impl Trait for Type";
                 return Err(Error::new_spanned(span, msg));
             }
-            for assoc in &item.items {
-                if let ImplItem::Const(assoc) = assoc {
-                    let const_token = assoc.const_token;
-                    let semi_token = assoc.semi_token;
-                    let span = quote!(#const_token #semi_token);
-                    let msg = "typetag trait with associated const is not supported yet";
-                    return Err(Error::new_spanned(span, msg));
-                }
-            }
             attrs.extend(item.attrs);
             item.attrs = attrs;
             Ok(Input::Impl(item))
@@ -96,95 +160,450 @@ impl Parse for Input {
 // #[typetag::serde]
 // #[typetag::serde(tag = "type")]
 // #[typetag::serde(tag = "type", default_variant = "default")]
+// #[typetag::serde(tag = "type", value_key = "data")]
+// #[typetag::serde(tag = "type", unknown = "Unknown")]
+// #[typetag::serde(untagged)]
+// #[typetag::serde(variant)]
+// #[typetag::serde(tag_kind = "id")]
 // #[typetag::serde(tag = "type", content = "content")]
 // #[typetag::serde(tag = "type", content = "content", deny_unknown_fields)]
 // #[typetag::serde(tag = "type", content = "content", default_variant = "default")]
+// #[typetag::serde(instantiate(Trait<u8>, Trait<u16>))]
+// #[typetag::serde(rename_all = "snake_case")]
+// #[typetag::serde(name_strategy = "path")]
+// #[typetag::serde(seed = Context)]
+// #[typetag::serde(borrow)]
+// #[typetag::serde(derive(Debug, PartialEq, Eq, Hash))]
+// #[typetag::serde(schema)]
 impl Parse for TraitArgs {
     fn parse(input: ParseStream) -> Result<Self> {
-        if input.is_empty() {
-            return Ok(TraitArgs::External);
+        let mut tag: Option<LitStr> = None;
+        let mut content: Option<LitStr> = None;
+        let mut value_key: Option<LitStr> = None;
+        let mut untagged: Option<kw::untagged> = None;
+        let mut variant: Option<kw::variant> = None;
+        let mut default_variant: Option<LitStr> = None;
+        let mut deny_unknown_fields: Option<kw::deny_unknown_fields> = None;
+        let mut instantiate: Option<Vec<Path>> = None;
+        let mut rename_all: Option<RenameRule> = None;
+        let mut name_strategy: Option<NameStrategy> = None;
+        let mut tag_kind: Option<(LitStr, TagKind)> = None;
+        let mut unknown: Option<LitStr> = None;
+        let mut seed: Option<Type> = None;
+        let mut borrow: Option<kw::borrow> = None;
+        let mut derives: Option<Vec<Derive>> = None;
+        let mut schema: Option<kw::schema> = None;
+
+        while !input.is_empty() {
+            let lookahead = input.lookahead1();
+            if lookahead.peek(kw::tag) {
+                let kw: kw::tag = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if tag.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `tag` argument"));
+                }
+                tag = Some(input.parse()?);
+            } else if lookahead.peek(kw::content) {
+                let kw: kw::content = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if content.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `content` argument"));
+                }
+                content = Some(input.parse()?);
+            } else if lookahead.peek(kw::untagged) {
+                let kw: kw::untagged = input.parse()?;
+                if untagged.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `untagged` argument"));
+                }
+                untagged = Some(kw);
+            } else if lookahead.peek(kw::variant) {
+                let kw: kw::variant = input.parse()?;
+                if variant.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `variant` argument"));
+                }
+                variant = Some(kw);
+            } else if lookahead.peek(kw::unknown) {
+                let kw: kw::unknown = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if unknown.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `unknown` argument"));
+                }
+                unknown = Some(input.parse()?);
+            } else if lookahead.peek(kw::value_key) {
+                let kw: kw::value_key = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if value_key.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `value_key` argument"));
+                }
+                value_key = Some(input.parse()?);
+            } else if lookahead.peek(kw::default_variant) {
+                let kw: kw::default_variant = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if default_variant.is_some() {
+                    let msg = "duplicate `default_variant` argument";
+                    return Err(Error::new_spanned(kw, msg));
+                }
+                default_variant = Some(input.parse()?);
+            } else if lookahead.peek(kw::deny_unknown_fields) {
+                let kw: kw::deny_unknown_fields = input.parse()?;
+                if deny_unknown_fields.is_some() {
+                    let msg = "duplicate `deny_unknown_fields` argument";
+                    return Err(Error::new_spanned(kw, msg));
+                }
+                deny_unknown_fields = Some(kw);
+            } else if lookahead.peek(kw::instantiate) {
+                let kw: kw::instantiate = input.parse()?;
+                if instantiate.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `instantiate` argument"));
+                }
+                let paths;
+                parenthesized!(paths in input);
+                let paths = Punctuated::<Path, Token![,]>::parse_terminated(&paths)?;
+                if paths.is_empty() {
+                    let msg = "expected at least one instantiation";
+                    return Err(Error::new_spanned(kw, msg));
+                }
+                instantiate = Some(paths.into_iter().collect());
+            } else if lookahead.peek(kw::derive) {
+                let kw: kw::derive = input.parse()?;
+                if derives.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `derive` argument"));
+                }
+                let idents;
+                parenthesized!(idents in input);
+                let idents = Punctuated::<Ident, Token![,]>::parse_terminated(&idents)?;
+                let mut list = Vec::new();
+                for ident in idents {
+                    let derive = match ident.to_string().as_str() {
+                        "Debug" => Derive::Debug,
+                        "PartialEq" => Derive::PartialEq,
+                        "Eq" => Derive::Eq,
+                        "Hash" => Derive::Hash,
+                        other => {
+                            let msg = format!(
+                                "unsupported derive `{other}`, \
+                                 expected Debug, PartialEq, Eq or Hash",
+                            );
+                            return Err(Error::new_spanned(ident, msg));
+                        }
+                    };
+                    if list.iter().any(|(prev, _ident)| *prev == derive) {
+                        return Err(Error::new_spanned(ident, "duplicate derive"));
+                    }
+                    list.push((derive, ident));
+                }
+                let has_partial_eq = list.iter().any(|(derive, _)| *derive == Derive::PartialEq);
+                if let Some((_, eq)) = list.iter().find(|(derive, _)| *derive == Derive::Eq) {
+                    if !has_partial_eq {
+                        let msg = "Eq requires PartialEq to be derived as well";
+                        return Err(Error::new_spanned(eq, msg));
+                    }
+                }
+                let list: Vec<Derive> = list.into_iter().map(|(derive, _)| derive).collect();
+                if list.is_empty() {
+                    return Err(Error::new_spanned(
+                        kw,
+                        "expected at least one trait to derive",
+                    ));
+                }
+                derives = Some(list);
+            } else if lookahead.peek(kw::name_strategy) {
+                let kw: kw::name_strategy = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if name_strategy.is_some() {
+                    let msg = "duplicate `name_strategy` argument";
+                    return Err(Error::new_spanned(kw, msg));
+                }
+                let strategy: LitStr = input.parse()?;
+                name_strategy = Some(match strategy.value().as_str() {
+                    "type" => NameStrategy::Type,
+                    "path" => NameStrategy::Path,
+                    other => {
+                        let msg = format!(
+                            "unknown name strategy `name_strategy = {other:?}`, \
+                             expected \"type\" or \"path\"",
+                        );
+                        return Err(Error::new_spanned(strategy, msg));
+                    }
+                });
+            } else if lookahead.peek(kw::tag_kind) {
+                let kw: kw::tag_kind = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if tag_kind.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `tag_kind` argument"));
+                }
+                let kind: LitStr = input.parse()?;
+                let value = match kind.value().as_str() {
+                    "name" => TagKind::Name,
+                    "id" => TagKind::Id,
+                    other => {
+                        let msg = format!(
+                            "unknown tag kind `tag_kind = {other:?}`, \
+                             expected \"name\" or \"id\"",
+                        );
+                        return Err(Error::new_spanned(kind, msg));
+                    }
+                };
+                tag_kind = Some((kind, value));
+            } else if lookahead.peek(kw::borrow) {
+                let kw: kw::borrow = input.parse()?;
+                if borrow.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `borrow` argument"));
+                }
+                borrow = Some(kw);
+            } else if lookahead.peek(kw::schema) {
+                let kw: kw::schema = input.parse()?;
+                if schema.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `schema` argument"));
+                }
+                schema = Some(kw);
+            } else if lookahead.peek(kw::seed) {
+                let kw: kw::seed = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if seed.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `seed` argument"));
+                }
+                seed = Some(input.parse()?);
+            } else if lookahead.peek(kw::rename_all) {
+                let kw: kw::rename_all = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if rename_all.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `rename_all` argument"));
+                }
+                let rule: LitStr = input.parse()?;
+                match RenameRule::from_str(&rule.value()) {
+                    Some(rule) => rename_all = Some(rule),
+                    None => {
+                        let expected: Vec<String> = RULES
+                            .iter()
+                            .map(|(name, _rule)| format!("{name:?}"))
+                            .collect();
+                        let msg = format!(
+                            "unknown rename rule `rename_all = {:?}`, expected one of {}",
+                            rule.value(),
+                            expected.join(", "),
+                        );
+                        return Err(Error::new_spanned(rule, msg));
+                    }
+                }
+            } else {
+                return Err(lookahead.error());
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
         }
 
-        input.parse::<kw::tag>()?;
-        input.parse::<Token![=]>()?;
-        let tag: LitStr = input.parse()?;
-        if !input.is_empty() {
-            input.parse::<Token![,]>()?;
+        if let (Some(untagged), Some(_)) = (untagged, &tag) {
+            let msg = "untagged cannot be combined with a tag";
+            return Err(Error::new_spanned(untagged, msg));
         }
-        if input.is_empty() {
-            return Ok(TraitArgs::Internal {
-                tag,
-                default_variant: None,
-            });
+
+        if let (Some(variant), true) = (variant, tag.is_some() || untagged.is_some()) {
+            let msg = "variant cannot be combined with a tag or untagged";
+            return Err(Error::new_spanned(variant, msg));
         }
 
-        let lookahead = input.lookahead1();
-        if lookahead.peek(kw::content) {
-            input.parse::<kw::content>()?;
-            input.parse::<Token![=]>()?;
-            let content: LitStr = input.parse()?;
-            if !input.is_empty() {
-                input.parse::<Token![,]>()?;
-            }
+        if let (Some(_), Some((kind, TagKind::Name))) = (variant, &tag_kind) {
+            let msg = "variant uses the impl ids as variant indices";
+            return Err(Error::new_spanned(kind, msg));
+        }
+
+        if let (Some(unknown), true) = (&unknown, untagged.is_some() || variant.is_some()) {
+            let msg = "unknown cannot be combined with untagged or variant";
+            return Err(Error::new_spanned(unknown, msg));
+        }
+
+        if let (Some(_), Some((kind, TagKind::Id))) = (untagged, &tag_kind) {
+            let msg = "untagged trait objects have no tag to hold an id";
+            return Err(Error::new_spanned(kind, msg));
+        }
 
-            let mut default_variant = None;
-            let mut deny_unknown_fields = false;
-            while !input.is_empty() {
-                let lookahead = input.lookahead1();
-                if default_variant.is_none() && lookahead.peek(kw::default_variant) {
-                    input.parse::<kw::default_variant>()?;
-                    input.parse::<Token![=]>()?;
-                    default_variant = Some(input.parse()?);
-                } else if !deny_unknown_fields && lookahead.peek(kw::deny_unknown_fields) {
-                    input.parse::<kw::deny_unknown_fields>()?;
-                    deny_unknown_fields = true;
+        if let (Some(borrow), true) = (borrow, seed.is_some() || instantiate.is_some()) {
+            let msg = "borrow cannot be combined with seed or instantiate";
+            return Err(Error::new_spanned(borrow, msg));
+        }
+
+        if let (Some(schema), true) = (schema, borrow.is_some() || unknown.is_some()) {
+            let msg = "schema cannot be combined with borrow or unknown";
+            return Err(Error::new_spanned(schema, msg));
+        }
+
+        if let (Some(value_key), false) = (&value_key, tag.is_some() && content.is_none()) {
+            let msg = "value_key requires an internal tag, without content";
+            return Err(Error::new_spanned(value_key, msg));
+        }
+
+        let repr = match (tag, content) {
+            (None, None) => {
+                if let Some(default_variant) = default_variant {
+                    let msg = "default_variant requires a tag";
+                    return Err(Error::new_spanned(default_variant, msg));
+                }
+                if let Some(deny_unknown_fields) = deny_unknown_fields {
+                    let msg = "deny_unknown_fields requires an adjacent tag and content";
+                    return Err(Error::new_spanned(deny_unknown_fields, msg));
+                }
+                if untagged.is_some() {
+                    Repr::Untagged
+                } else if variant.is_some() {
+                    Repr::Variant
                 } else {
-                    return Err(lookahead.error());
+                    Repr::External
+                }
+            }
+            (Some(tag), None) => {
+                if let Some(deny_unknown_fields) = deny_unknown_fields {
+                    let msg = "deny_unknown_fields requires an adjacent tag and content";
+                    return Err(Error::new_spanned(deny_unknown_fields, msg));
                 }
-                if !input.is_empty() {
-                    input.parse::<Token![,]>()?;
+                Repr::Internal {
+                    tag,
+                    value_key,
+                    default_variant,
                 }
             }
-
-            Ok(TraitArgs::Adjacent {
+            (Some(tag), Some(content)) => Repr::Adjacent {
                 tag,
                 content,
                 default_variant,
-                deny_unknown_fields,
-            })
-        } else if lookahead.peek(kw::default_variant) {
-            input.parse::<kw::default_variant>()?;
-            input.parse::<Token![=]>()?;
-            let default_variant: LitStr = input.parse()?;
-            input.parse::<Option<Token![,]>>()?;
-            return Ok(TraitArgs::Internal {
-                tag,
-                default_variant: Some(default_variant),
-            });
-        } else {
-            Err(lookahead.error())
-        }
+                deny_unknown_fields: deny_unknown_fields.is_some(),
+            },
+            (None, Some(content)) => {
+                let msg = "content requires a tag";
+                return Err(Error::new_spanned(content, msg));
+            }
+        };
+
+        Ok(TraitArgs {
+            repr,
+            rename_all: rename_all.unwrap_or(RenameRule::None),
+            name_strategy: name_strategy.unwrap_or(NameStrategy::Type),
+            tag_kind: match (tag_kind, variant) {
+                (Some((_kind, value)), _) => value,
+                (None, Some(_)) => TagKind::Id,
+                (None, None) => TagKind::Name,
+            },
+            unknown,
+            seed,
+            borrow: borrow.is_some(),
+            instantiate: instantiate.unwrap_or_default(),
+            derives: derives.unwrap_or_default(),
+            schema: schema.is_some(),
+        })
     }
 }
 
 // #[typetag::serde]
 // #[typetag::serde(name = "Tag")]
+// #[typetag::serde(name = "Tag", alias = "OldTag", alias = "OlderTag")]
+// #[typetag::serde(name = "Tag", override)]
+// #[typetag::serde(id = 17)]
+// #[typetag::serde(instantiate(Struct<u8> = "Tag8", Struct<u16> = "Tag16"))]
 impl Parse for ImplArgs {
     fn parse(input: ParseStream) -> Result<Self> {
-        let name = if input.is_empty() {
-            None
-        } else {
-            input.parse::<kw::name>()?;
-            input.parse::<Token![=]>()?;
-            let name: LitStr = input.parse()?;
-            input.parse::<Option<Token![,]>>()?;
-            Some(name)
-        };
-        Ok(ImplArgs { name })
+        let mut name: Option<LitStr> = None;
+        let mut aliases: Vec<LitStr> = Vec::new();
+        let mut is_override: Option<Token![override]> = None;
+        let mut id: Option<LitInt> = None;
+        let mut instantiate: Option<Vec<Instantiation>> = None;
+
+        while !input.is_empty() {
+            let lookahead = input.lookahead1();
+            if lookahead.peek(kw::name) {
+                let kw: kw::name = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if name.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `name` argument"));
+                }
+                name = Some(input.parse()?);
+            } else if lookahead.peek(kw::alias) {
+                input.parse::<kw::alias>()?;
+                input.parse::<Token![=]>()?;
+                let alias: LitStr = input.parse()?;
+                if aliases.iter().any(|prev| prev.value() == alias.value()) {
+                    return Err(Error::new_spanned(alias, "duplicate alias"));
+                }
+                aliases.push(alias);
+            } else if lookahead.peek(kw::id) {
+                let kw: kw::id = input.parse()?;
+                input.parse::<Token![=]>()?;
+                if id.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `id` argument"));
+                }
+                let lit: LitInt = input.parse()?;
+                lit.base10_parse::<u64>()?;
+                id = Some(lit);
+            } else if lookahead.peek(Token![override]) {
+                let kw: Token![override] = input.parse()?;
+                if is_override.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `override` argument"));
+                }
+                is_override = Some(kw);
+            } else if lookahead.peek(kw::instantiate) {
+                let kw: kw::instantiate = input.parse()?;
+                if instantiate.is_some() {
+                    return Err(Error::new_spanned(kw, "duplicate `instantiate` argument"));
+                }
+                let content;
+                parenthesized!(content in input);
+                let list = Punctuated::<Instantiation, Token![,]>::parse_terminated(&content)?;
+                if list.is_empty() {
+                    let msg = "expected at least one instantiation";
+                    return Err(Error::new_spanned(kw, msg));
+                }
+                instantiate = Some(list.into_iter().collect());
+            } else {
+                return Err(lookahead.error());
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        if let (Some(name), Some(_)) = (&name, &instantiate) {
+            let msg = "name cannot be combined with instantiate; \
+                       give each instantiation its own name instead";
+            return Err(Error::new_spanned(name, msg));
+        }
+
+        if let (Some(id), Some(_)) = (&id, &instantiate) {
+            let msg = "id cannot be combined with instantiate";
+            return Err(Error::new_spanned(id, msg));
+        }
+
+        if let (Some(alias), Some(_)) = (aliases.first(), &instantiate) {
+            let msg = "alias cannot be combined with instantiate";
+            return Err(Error::new_spanned(alias, msg));
+        }
+
+        if let Some(name) = &name {
+            if let Some(alias) = aliases.iter().find(|alias| alias.value() == name.value()) {
+                let msg = "alias is the same as the name";
+                return Err(Error::new_spanned(alias, msg));
+            }
+        }
+
+        Ok(ImplArgs {
+            name,
+            aliases,
+            is_override: is_override.is_some(),
+            id,
+            instantiate: instantiate.unwrap_or_default(),
+        })
+    }
+}
+
+impl Parse for Instantiation {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let ty: Type = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let name: LitStr = input.parse()?;
+        Ok(Instantiation { ty, name })
     }
 }
 
-fn is_self_sized(generics: &Generics) -> bool {
+pub fn is_self_sized(generics: &Generics) -> bool {
     if let Some(where_clause) = &generics.where_clause {
         for predicate in &where_clause.predicates {
             if let WherePredicate::Type(pred_type) = predicate {
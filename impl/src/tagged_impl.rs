@@ -1,61 +1,424 @@
+use crate::case::RenameRule;
+use crate::instantiate;
 use crate::{ImplArgs, Mode};
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{parse_quote, Error, ItemImpl, Type, TypePath};
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Error, GenericArgument, GenericParam, Generics, ImplItem, ItemImpl, Lifetime,
+    LitInt, LitStr, Path, PathArguments, Type, TypePath,
+};
 
 pub(crate) fn expand(args: ImplArgs, mut input: ItemImpl, mode: Mode) -> TokenStream {
-    if mode.de && !input.generics.params.is_empty() {
-        let msg = "deserialization of generic impls is not supported yet; \
+    // An impl with one lifetime parameter, like `impl<'de> Event<'de> for
+    // Line<'de>`, is of a trait that borrows from the input. It deserializes
+    // for every lifetime of the input.
+    let borrowed = match input.generics.params.first() {
+        Some(GenericParam::Lifetime(param)) if input.generics.params.len() == 1 => {
+            Some(param.lifetime.clone())
+        }
+        _ => None,
+    };
+
+    if let Some(instantiation) = args.instantiate.first() {
+        if input.generics.params.is_empty() {
+            let msg = "instantiate is only supported on generic impls";
+            return Error::new_spanned(&instantiation.ty, msg).to_compile_error();
+        }
+        if let Some(lifetime) = input.generics.lifetimes().next() {
+            let msg = "instantiate is not supported on impls with lifetime parameters";
+            return Error::new_spanned(lifetime, msg).to_compile_error();
+        }
+    } else if mode.de && !input.generics.params.is_empty() && borrowed.is_none() {
+        let msg = "deserialization of generic impls requires listing the instantiations to \
+                   register, as in #[typetag::serde(instantiate(Struct<u8> = \"Name\"))]; \
                    use #[typetag::serialize] to generate serialization only";
         return Error::new_spanned(input.generics, msg).to_compile_error();
     }
 
-    let name = match args.name {
-        Some(name) => quote!(#name),
-        None => match type_name(&input.self_ty) {
+    let object = input.trait_.as_ref().unwrap().1.clone();
+
+    // Outside of the impl, the trait object and self type are named for the
+    // 'static input.
+    let mut static_object = object.clone();
+    let mut static_self = (*input.self_ty).clone();
+    if let Some(lifetime) = &borrowed {
+        let mut visitor = StaticLifetime(lifetime);
+        visitor.visit_path_mut(&mut static_object);
+        visitor.visit_type_mut(&mut static_self);
+    }
+
+    // Associated types of the impl, which the trait object is bound by. For
+    // example an impl with `type Output = f64` registers into the trait object
+    // `dyn Trait<Output = f64>`.
+    let assoc_types: Vec<(Ident, Type)> = input
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Type(assoc) if assoc.generics.params.is_empty() => {
+                Some((assoc.ident.clone(), assoc.ty.clone()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let mut expanded = TokenStream::new();
+
+    let name = if args.instantiate.is_empty() {
+        match args.name {
             Some(name) => quote!(#name),
-            None => {
-                let msg = "use #[typetag::serde(name = \"...\")] to specify a unique name";
-                return Error::new_spanned(&input.self_ty, msg).to_compile_error();
+            None => match type_name(&input.self_ty) {
+                Some(name) => {
+                    let object = registered_object(&static_object, &assoc_types, &static_self);
+                    if !assoc_types.is_empty() {
+                        input.attrs.push(
+                            parse_quote!(#[allow(unknown_lints, unused_associated_type_bounds)]),
+                        );
+                    }
+                    let generics = match borrowed {
+                        Some(_) => Generics::default(),
+                        None => input.generics.clone(),
+                    };
+                    expanded.extend(default_name(&object, &static_self, &generics, &name));
+                    quote!(<#static_self as typetag::__private::InstanceName<dyn #object>>::NAME)
+                }
+                None => {
+                    let msg = "use #[typetag::serde(name = \"...\")] to specify a unique name";
+                    return Error::new_spanned(&input.self_ty, msg).to_compile_error();
+                }
+            },
+        }
+    } else {
+        let bound_object = bind_assoc_types(&object, &assoc_types);
+
+        // Each instantiation carries its own name, which the generic impl
+        // looks up by comparing its self type against the instantiations.
+        let mut instances = Vec::new();
+        for instantiation in &args.instantiate {
+            let mut bindings =
+                match instantiate::unify(&input.generics, &input.self_ty, &instantiation.ty) {
+                    Ok(bindings) => bindings,
+                    Err(err) => return err.to_compile_error(),
+                };
+
+            let mut object = object.clone();
+            instantiate::substitute(&mut object, &bindings);
+            let this = &instantiation.ty;
+            let name = &instantiation.name;
+
+            let self_ident = Ident::new("Self", Span::call_site());
+            bindings.insert(self_ident, GenericArgument::Type(this.clone()));
+            let mut instance_object = bound_object.clone();
+            instantiate::substitute(&mut instance_object, &bindings);
+            instances.push(quote! {
+                (
+                    typetag::__private::TypeId::of::<#this>(),
+                    <#this as typetag::__private::InstanceName<dyn #instance_object>>::NAME,
+                )
+            });
+
+            expanded.extend(quote! {
+                #[allow(unknown_lints, unused_associated_type_bounds)]
+                impl typetag::__private::InstanceName<dyn #instance_object> for #this {
+                    const NAME: &'static str = #name;
+                }
+            });
+
+            if mode.de && cfg!(feature = "runtime-registration") {
+                expanded.extend(quote! {
+                    #[allow(unknown_lints, unused_associated_type_bounds)]
+                    impl typetag::__private::InstanceId<dyn #instance_object> for #this {
+                        const ID: u64 = typetag::__private::hash_id(#name);
+                    }
+                });
             }
-        },
+
+            if mode.de && !cfg!(feature = "runtime-registration") {
+                expanded.extend(register(
+                    &object,
+                    &assoc_types,
+                    this,
+                    &quote!(#name),
+                    &[],
+                    args.is_override,
+                    &quote!(typetag::__private::hash_id(#name)),
+                ));
+            }
+        }
+
+        if !assoc_types.is_empty() {
+            input
+                .attrs
+                .push(parse_quote!(#[allow(unknown_lints, unused_associated_type_bounds)]));
+        }
+
+        // Instantiations are told apart by TypeId, which needs them 'static.
+        let self_ty = &input.self_ty;
+        input
+            .generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(#self_ty: 'static));
+
+        quote! {
+            typetag::__private::instance_name(
+                typetag::__private::TypeId::of::<Self>(),
+                &[#(#instances),*],
+            )
+        }
     };
 
-    augment_impl(&mut input, &name, mode);
+    if let Some(id) = &args.id {
+        let object = registered_object(&static_object, &assoc_types, &static_self);
+        expanded.extend(quote_spanned! {id.span()=>
+            #[allow(unknown_lints, unused_associated_type_bounds)]
+            const _: () = typetag::__private::require_tag_kind_id(<dyn #object>::TYPETAG_TAGGED_BY_ID);
+        });
+    }
+
+    // Only an instantiation that is not listed goes without a tag.
+    let tag = if args.instantiate.is_empty() {
+        quote!(typetag::__private::Option::Some(#name))
+    } else {
+        name.clone()
+    };
+    augment_impl(&mut input, &tag, args.id.as_ref(), mode);
 
-    let object = &input.trait_.as_ref().unwrap().1;
-    let this = &input.self_ty;
+    if mode.de && args.instantiate.is_empty() {
+        // Without an explicit id, the id is derived from the name at compile
+        // time. Traits that are not tagged by id ignore it.
+        let id = match &args.id {
+            Some(id) => quote!(#id),
+            None => quote!(typetag::__private::hash_id(#name)),
+        };
+        if let Some(lifetime) = &borrowed {
+            if !cfg!(feature = "runtime-registration") {
+                expanded.extend(register_borrowed(
+                    &static_object,
+                    &input.self_ty,
+                    &static_self,
+                    lifetime,
+                    &name,
+                    &args.aliases,
+                    args.is_override,
+                    &id,
+                ));
+            }
+        } else if cfg!(feature = "runtime-registration") {
+            let bound_object = bind_assoc_types(&object, &assoc_types);
+            let self_ty = &input.self_ty;
+            expanded.extend(quote! {
+                #[allow(unknown_lints, unused_associated_type_bounds)]
+                impl typetag::__private::InstanceId<dyn #bound_object> for #self_ty {
+                    const ID: u64 = #id;
+                }
+            });
+        } else {
+            expanded.extend(register(
+                &object,
+                &assoc_types,
+                &input.self_ty,
+                &name,
+                &args.aliases,
+                args.is_override,
+                &id,
+            ));
+        }
+    }
 
-    let mut expanded = quote! {
+    quote! {
         #input
+        #expanded
+    }
+}
+
+// Tag of an impl without a name, built at compile time by the rule of the
+// trait out of the type name, which is split into words by its uppercase
+// letters. The trait object of a generic impl cannot be named where the tag
+// is built, so the trait picks among the tags under every rule instead.
+fn default_name(object: &Path, this: &Type, generics: &Generics, name: &str) -> TokenStream {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let tag = if generics.params.is_empty() {
+        let words = name
+            .char_indices()
+            .filter(|(i, ch)| *i > 0 && ch.is_uppercase())
+            .map(|(i, _ch)| i);
+        quote! {
+            {
+                const DEFAULT_NAME: typetag::__private::DefaultName = <dyn #object>::TYPETAG_DEFAULT_NAME;
+                const MODULE_PATH: &str = typetag::__private::module_path!();
+                const WORDS: &[usize] = &[#(#words),*];
+                const LEN: usize = DEFAULT_NAME.len(MODULE_PATH, #name, WORDS);
+                const TAG: [u8; LEN] = DEFAULT_NAME.build(MODULE_PATH, #name, WORDS);
+                const NAME: &str = typetag::__private::DefaultName::as_str(&TAG);
+                NAME
+            }
+        }
+    } else {
+        let names = RenameRule::ALL.iter().map(|rule| rule.apply(name));
+        let paths = names.clone();
+        quote! {
+            typetag::__private::DefaultName::select(
+                <dyn #object>::TYPETAG_DEFAULT_NAME,
+                [#(#names),*],
+                [#(
+                    typetag::__private::concat!(
+                        typetag::__private::module_path!(),
+                        "::",
+                        #paths,
+                    )
+                ),*],
+            )
+        }
     };
 
-    if mode.de {
-        expanded.extend(quote! {
+    quote! {
+        #[allow(unknown_lints, unused_associated_type_bounds)]
+        impl #impl_generics typetag::__private::InstanceName<dyn #object> for #this #where_clause {
+            const NAME: &'static str = #tag;
+        }
+    }
+}
+
+fn register(
+    object: &Path,
+    assoc_types: &[(Ident, Type)],
+    this: &Type,
+    name: &TokenStream,
+    aliases: &[LitStr],
+    is_override: bool,
+    id: &TokenStream,
+) -> TokenStream {
+    let object = registered_object(object, assoc_types, this);
+
+    quote! {
+        #[allow(unknown_lints, unused_associated_type_bounds)]
+        const _: () = {
             typetag::__private::inventory::submit! {
-                <dyn #object>::typetag_register(
+                <dyn #object>::typetag_register::<#this>(
                     #name,
-                    (|deserializer| typetag::__private::Result::Ok(
-                        typetag::__private::Box::new(
-                            typetag::__private::erased_serde::deserialize::<#this>(deserializer)?
-                        ),
-                    )) as typetag::__private::DeserializeFn<<dyn #object as typetag::__private::Strictest>::Object>,
+                    &[#(#aliases),*],
+                    typetag::__private::type_name::<#this>,
+                    typetag::__private::module_path!(),
+                    #is_override,
+                    #id,
                 )
             }
-        });
+        };
     }
+}
+
+// Registration of an impl that borrows from the input, with a deserialize
+// function for any lifetime of the input.
+#[allow(clippy::too_many_arguments)]
+fn register_borrowed(
+    static_object: &Path,
+    this: &Type,
+    static_self: &Type,
+    lifetime: &Lifetime,
+    name: &TokenStream,
+    aliases: &[LitStr],
+    is_override: bool,
+    id: &TokenStream,
+) -> TokenStream {
+    let schema = if cfg!(feature = "schemars") {
+        quote!(schema: typetag::__private::Option::None,)
+    } else {
+        quote!()
+    };
+
+    quote! {
+        const _: () = {
+            type TypetagObject = <dyn #static_object as typetag::__private::Strictest>::Object;
+
+            fn deserialize_fn() -> typetag::__private::DeserializeFn<TypetagObject> {
+                fn boxed<'typetag, #lifetime>(
+                    _context: &'typetag typetag::__private::Context<'typetag, TypetagObject>,
+                    deserializer: &mut dyn typetag::__private::erased_serde::Deserializer<#lifetime>,
+                ) -> typetag::__private::erased_serde::Result<
+                    typetag::__private::Box<typetag::__private::Object<#lifetime, TypetagObject>>,
+                > {
+                    typetag::__private::Result::Ok(typetag::__private::Box::new(
+                        typetag::__private::erased_serde::deserialize::<#this>(deserializer)?,
+                    ))
+                }
 
-    expanded
+                typetag::__private::DeserializeFn {
+                    boxed,
+                    #schema
+                }
+            }
+
+            typetag::__private::inventory::submit! {
+                <dyn #static_object>::typetag_register_borrowed(
+                    #name,
+                    &[#(#aliases),*],
+                    typetag::__private::type_name::<#static_self>,
+                    typetag::__private::module_path!(),
+                    #is_override,
+                    #id,
+                    deserialize_fn,
+                )
+            }
+        };
+    }
+}
+
+struct StaticLifetime<'a>(&'a Lifetime);
+
+impl VisitMut for StaticLifetime<'_> {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident == self.0.ident {
+            *lifetime = Lifetime::new("'static", lifetime.span());
+        }
+    }
+}
+
+// The trait object as named by an impl, with each associated type bound to
+// its definition in the impl.
+fn registered_object(object: &Path, assoc_types: &[(Ident, Type)], this: &Type) -> Path {
+    let projections: Vec<(Ident, Type)> = assoc_types
+        .iter()
+        .map(|(ident, _)| (ident.clone(), parse_quote!(<#this as #object>::#ident)))
+        .collect();
+    bind_assoc_types(object, &projections)
 }
 
-fn augment_impl(input: &mut ItemImpl, name: &TokenStream, mode: Mode) {
+fn bind_assoc_types(object: &Path, assoc_types: &[(Ident, Type)]) -> Path {
+    let mut object = object.clone();
+    if assoc_types.is_empty() {
+        return object;
+    }
+    let segment = object.segments.last_mut().unwrap();
+    if let PathArguments::None = segment.arguments {
+        segment.arguments = PathArguments::AngleBracketed(parse_quote!(<>));
+    }
+    if let PathArguments::AngleBracketed(args) = &mut segment.arguments {
+        for (ident, ty) in assoc_types {
+            args.args.push(parse_quote!(#ident = #ty));
+        }
+    }
+    object
+}
+
+fn augment_impl(input: &mut ItemImpl, tag: &TokenStream, id: Option<&LitInt>, mode: Mode) {
     if mode.ser {
         input.items.push(parse_quote! {
             #[doc(hidden)]
-            fn typetag_name(&self) -> &'static str {
-                #name
+            fn typetag_name(&self) -> typetag::__private::Option<&'static str> {
+                #tag
             }
         });
+
+        if let Some(id) = id {
+            input.items.push(parse_quote! {
+                #[doc(hidden)]
+                fn typetag_id(&self, _name: &'static str) -> u64 {
+                    #id
+                }
+            });
+        }
     }
 
     if mode.de {
@@ -1,116 +1,439 @@
+use crate::case::RenameRule;
+use crate::parse::{self, Derive, NameStrategy, Repr, TagKind};
 use crate::{Mode, TraitArgs};
-use proc_macro2::TokenStream;
-use quote::quote;
-use syn::{parse_quote, Error, ItemTrait, LitStr, TraitBoundModifier, TypeParamBound};
+use proc_macro2::{Ident, TokenStream};
+use quote::{format_ident, quote};
+use syn::punctuated::Punctuated;
+use syn::visit_mut::VisitMut;
+use syn::{
+    parse_quote, Error, GenericParam, Generics, ItemTrait, Lifetime, LitStr, Path, Token,
+    TraitBoundModifier, TraitItem, Type, TypeParamBound, WhereClause, WherePredicate,
+};
 
 pub(crate) fn expand(args: TraitArgs, mut input: ItemTrait, mode: Mode) -> TokenStream {
-    if mode.de && !input.generics.params.is_empty() {
-        let msg = "deserialization of generic traits is not supported yet; \
-                   use #[typetag::serialize] to generate serialization only";
-        return Error::new_spanned(input.generics, msg).to_compile_error();
+    let assoc_types = associated_types(&input);
+    let unsized_assoc = assoc_types.iter().find(|assoc| !assoc.sized);
+
+    if !mode.ser && !args.derives.is_empty() {
+        let msg = "derive works from the serialized impl, which requires \
+                   #[typetag::serde] or #[typetag::serialize]";
+        return Error::new_spanned(&input.ident, msg).to_compile_error();
+    }
+
+    if args.schema {
+        if !cfg!(feature = "schemars") {
+            let msg = "schema requires the schemars feature of typetag";
+            return Error::new_spanned(&input.ident, msg).to_compile_error();
+        }
+        if !mode.de {
+            let msg = "schema describes the input of deserialization, which requires \
+                       #[typetag::serde] or #[typetag::deserialize]";
+            return Error::new_spanned(&input.ident, msg).to_compile_error();
+        }
+    }
+
+    if mode.de && args.borrow {
+        if cfg!(feature = "runtime-registration") {
+            let msg = "borrow is not supported with the runtime-registration feature";
+            return Error::new_spanned(&input.ident, msg).to_compile_error();
+        }
+        if input.generics.params.len() != 1 || input.generics.lifetimes().count() != 1 {
+            let msg = "borrow requires a trait with one lifetime parameter for the input, \
+                       as in `trait Event<'de>`";
+            return Error::new_spanned(&input.ident, msg).to_compile_error();
+        }
+        if let Some(assoc) = unsized_assoc {
+            let msg = "borrow is not supported on traits with associated types";
+            return Error::new_spanned(&assoc.ident, msg).to_compile_error();
+        }
+    }
+
+    if mode.de && args.instantiate.is_empty() && !args.borrow {
+        if !input.generics.params.is_empty() {
+            let msg = "deserialization of generic traits requires listing the trait objects to \
+                       support, as in #[typetag::serde(instantiate(Trait<u8>))]; \
+                       use #[typetag::serialize] to generate serialization only";
+            return Error::new_spanned(input.generics, msg).to_compile_error();
+        }
+        if let Some(assoc) = unsized_assoc {
+            let msg = "deserialization of traits with associated types requires listing the \
+                       trait objects to support, as in \
+                       #[typetag::serde(instantiate(Trait<Assoc = u8>))]; \
+                       use #[typetag::serialize] to generate serialization only";
+            return Error::new_spanned(&assoc.ident, msg).to_compile_error();
+        }
+    }
+
+    if let Some(path) = args.instantiate.first() {
+        if input.generics.params.is_empty() && unsized_assoc.is_none() {
+            let msg = "instantiate is only supported on generic traits and traits with \
+                       associated types";
+            return Error::new_spanned(path, msg).to_compile_error();
+        }
+    }
+
+    for path in &args.instantiate {
+        let segment = path.segments.last().unwrap();
+        if segment.ident != input.ident {
+            let msg = format!("expected an instantiation of `{}`", input.ident);
+            return Error::new_spanned(path, msg).to_compile_error();
+        }
     }
 
     augment_trait(&mut input, mode);
 
-    let (serialize_impl, deserialize_impl) = match args {
-        TraitArgs::External => externally_tagged(&input),
-        TraitArgs::Internal {
+    let id = serialized_id(&input, args.tag_kind);
+
+    let tagging = match &args.repr {
+        Repr::External | Repr::Untagged | Repr::Variant => quote!(External),
+        Repr::Internal { tag, .. } => quote!(Internal(#tag)),
+        Repr::Adjacent { tag, .. } => quote!(Adjacent(#tag)),
+    };
+
+    let schema_impl = schema_impl(&args.repr);
+
+    let (mut serialize_impl, mut deserialize_impl) = match args.repr {
+        Repr::External => externally_tagged(&id, &input),
+        Repr::Untagged => untagged(&input),
+        Repr::Variant => variant(&input),
+        Repr::Internal {
+            tag,
+            value_key,
+            default_variant,
+        } => internally_tagged(tag, value_key, default_variant, &id, &input),
+        Repr::Adjacent {
             tag,
+            content,
             default_variant,
-        } => internally_tagged(tag, default_variant, &input),
-        TraitArgs::Adjacent {
+            deny_unknown_fields,
+        } => adjacently_tagged(
             tag,
             content,
             default_variant,
             deny_unknown_fields,
-        } => adjacently_tagged(tag, content, default_variant, deny_unknown_fields, &input),
+            &id,
+            &input,
+        ),
     };
 
+    if let Some(unknown) = &args.unknown {
+        (serialize_impl, deserialize_impl) =
+            unknown_variant(unknown, &tagging, serialize_impl, deserialize_impl, &input);
+    }
+
     let object = &input.ident;
+    let markers = marker_supertraits(&input);
 
-    let mut expanded = TokenStream::new();
+    let mut expanded = object_consts(
+        &input,
+        &assoc_types,
+        args.rename_all,
+        args.name_strategy,
+        args.tag_kind,
+    );
 
     if mode.ser {
-        let mut impl_generics = input.generics.clone();
-        impl_generics.params.push(parse_quote!('typetag));
-        let (impl_generics, _, _) = impl_generics.split_for_impl();
+        let ident = &input.ident;
+
+        // Serialization is supported for every binding of the associated
+        // types, not only the ones registered for deserialization.
+        let object = trait_object(&input, assoc_types.iter().filter(|assoc| !assoc.sized));
+        let mut generics = input.generics.clone();
+        generics.params.push(parse_quote!('typetag));
+        for assoc in assoc_types.iter().filter(|assoc| !assoc.sized) {
+            generics.params.push(assoc.generic_param());
+        }
+        let (impl_generics, _, _) = generics.split_for_impl();
         let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+        let object_name = ident.to_string();
 
         expanded.extend(quote! {
             impl #impl_generics typetag::__private::serde::Serialize
-            for dyn #object #ty_generics + 'typetag #where_clause {
+            for dyn #object + 'typetag #where_clause {
                 fn serialize<S>(&self, serializer: S) -> typetag::__private::Result<S::Ok, S::Error>
                 where
                     S: typetag::__private::serde::Serializer,
                 {
+                    let name = match <Self as #ident #ty_generics>::typetag_name(self) {
+                        typetag::__private::Option::Some(name) => name,
+                        typetag::__private::Option::None => {
+                            return typetag::__private::Result::Err(typetag::__private::unlisted(#object_name));
+                        }
+                    };
                     #serialize_impl
                 }
             }
         });
 
-        for marker_traits in &[quote!(Send), quote!(Sync), quote!(Send + Sync)] {
+        // Send and Sync are supported even when the trait does not require
+        // them, along with every combination of its own marker supertraits.
+        let mut ser_markers = vec![Marker::Send, Marker::Sync];
+        for marker in &markers {
+            if !ser_markers.contains(marker) {
+                ser_markers.push(*marker);
+            }
+        }
+        for marker_traits in marker_subsets(&ser_markers).iter().skip(1) {
             expanded.extend(quote! {
                 impl #impl_generics typetag::__private::serde::Serialize
-                for dyn #object #ty_generics + #marker_traits + 'typetag #where_clause {
+                for dyn #object + #marker_traits + 'typetag #where_clause {
                     fn serialize<S>(&self, serializer: S) -> typetag::__private::Result<S::Ok, S::Error>
                     where
                         S: typetag::__private::serde::Serializer,
                     {
-                        typetag::__private::serde::Serialize::serialize(self as &dyn #object #ty_generics, serializer)
+                        typetag::__private::serde::Serialize::serialize(self as &dyn #object, serializer)
                     }
                 }
             });
         }
+
+        // Derived impls go on the same trait objects as Serialize.
+        let (_, ty_generics, _) = input.generics.split_for_impl();
+        let tagged_trait = quote!(#ident #ty_generics);
+        for marker_traits in marker_subsets(&ser_markers) {
+            let object = if marker_traits.is_empty() {
+                object.clone()
+            } else {
+                quote!(#object + #marker_traits)
+            };
+            for derive in &args.derives {
+                expanded.extend(derived_impl(
+                    *derive,
+                    &object,
+                    &tagged_trait,
+                    &generics,
+                    where_clause,
+                ));
+            }
+        }
     }
 
     if mode.de {
-        let registry = build_registry(&input);
-
-        let is_send = has_supertrait(&input, "Send");
-        let is_sync = has_supertrait(&input, "Sync");
-        let (strictest, others) = match (is_send, is_sync) {
-            (false, false) => (quote!(), vec![]),
-            (true, false) => (quote!(Send), vec![quote!()]),
-            (false, true) => (quote!(Sync), vec![quote!()]),
-            (true, true) => (
-                quote!(Send + Sync),
-                vec![quote!(), quote!(Send), quote!(Sync)],
-            ),
+        // The registry holds trait objects with every marker supertrait, which
+        // the trait objects with fewer markers upcast from.
+        let mut others = marker_subsets(&markers);
+        let strictest = others.pop().unwrap();
+
+        let instantiated = !args.instantiate.is_empty();
+
+        expanded.extend(build_registry(
+            &input,
+            &assoc_types,
+            args.borrow,
+            instantiated,
+        ));
+
+        // Impls of a generic trait may implement instantiations that are not
+        // listed, which register nothing but still name their trait object.
+        // So the trait objects of every instantiation get the same impls.
+        if instantiated {
+            expanded.extend(instantiations(
+                &input,
+                &assoc_types,
+                &others,
+                &strictest,
+                args.seed.as_ref(),
+                args.schema,
+            ));
+        }
+
+        // A generic trait gets one registry per listed instantiation. A trait
+        // that borrows from the input gets one for all lifetimes, keyed by its
+        // 'static trait object.
+        let objects = if args.borrow {
+            vec![quote!(#object<'static>)]
+        } else if args.instantiate.is_empty() {
+            vec![quote!(#object)]
+        } else {
+            args.instantiate.iter().map(|path| quote!(#path)).collect()
         };
 
-        expanded.extend(quote! {
-            #registry
+        for object in objects {
+            let registered = registered_impl(
+                &object,
+                &strictest,
+                args.tag_kind,
+                args.seed.as_ref(),
+                args.borrow,
+                args.schema,
+                instantiated,
+            );
 
-            impl typetag::__private::Strictest for dyn #object {
-                type Object = dyn #object + #strictest;
+            // The trait object deserialized from input that lives for 'de.
+            let deserialized = if args.borrow {
+                let ident = &input.ident;
+                quote!(#ident<'de> + 'de)
+            } else {
+                quote!(#object)
+            };
+
+            if !instantiated {
+                let context = context_type(args.seed.as_ref());
+                expanded.extend(quote! {
+                    impl typetag::__private::Strictest for dyn #object {
+                        type Object = dyn #object + #strictest;
+                    }
+
+                    impl<'de> typetag::__private::Lifetime<'de> for dyn #object + #strictest {
+                        type Object = dyn #deserialized + #strictest;
+                    }
+
+                    impl<'typetag> typetag::__private::Contextual<'typetag> for dyn #object + #strictest {
+                        type Context = #context;
+                    }
+                });
             }
+            expanded.extend(registered);
 
-            #[allow(unknown_lints, non_local_definitions)] // false positive: https://github.com/rust-lang/rust/issues/121621
-            impl<'de> typetag::__private::serde::Deserialize<'de> for typetag::__private::Box<dyn #object + #strictest> {
-                fn deserialize<D>(deserializer: D) -> typetag::__private::Result<Self, D::Error>
-                where
-                    D: typetag::__private::serde::Deserializer<'de>,
-                {
-                    #deserialize_impl
+            // A trait with a seed deserializes only through typetag::Seed, since
+            // its impls need the context.
+            if args.seed.is_some() {
+                expanded.extend(quote! {
+                    impl typetag::__private::Seeded for dyn #object + #strictest {
+                        fn deserialize_seeded<'de, 'typetag, D>(context: &'typetag typetag::__private::Context<'typetag, Self>, deserializer: D) -> typetag::__private::Result<typetag::__private::Box<Self>, D::Error>
+                        where
+                            D: typetag::__private::serde::Deserializer<'de>,
+                        {
+                            let registry = <dyn #object as typetag::__private::Registered>::registry();
+                            #deserialize_impl
+                        }
+                    }
+                });
+            } else {
+                expanded.extend(quote! {
+                    #[allow(unknown_lints, non_local_definitions)] // false positive: https://github.com/rust-lang/rust/issues/121621
+                    impl<'de> typetag::__private::serde::Deserialize<'de> for typetag::__private::Box<dyn #deserialized + #strictest> {
+                        fn deserialize<D>(deserializer: D) -> typetag::__private::Result<Self, D::Error>
+                        where
+                            D: typetag::__private::serde::Deserializer<'de>,
+                        {
+                            let registry = <dyn #object as typetag::__private::Registered>::registry();
+                            let context = &();
+                            #deserialize_impl
+                        }
+                    }
+                });
+            }
+
+            let runtime = cfg!(feature = "runtime-registration");
+            let forward_runtime = if runtime {
+                quote! {
+                    fn runtime() -> &'static typetag::__private::RuntimeRegistry<Self::Object> {
+                        <dyn #object as typetag::__private::Registered>::runtime()
+                    }
+                }
+            } else {
+                quote!()
+            };
+
+            for marker_traits in others.iter().chain(Some(&strictest)) {
+                if marker_traits.is_empty() {
+                    continue;
+                }
+                if !instantiated {
+                    expanded.extend(quote! {
+                        impl typetag::__private::Strictest for dyn #object + #marker_traits {
+                            type Object = dyn #object + #strictest;
+                        }
+                    });
+                }
+                expanded.extend(quote! {
+                    impl typetag::__private::Registered for dyn #object + #marker_traits {
+                        fn registry() -> &'static typetag::__private::Registry<Self::Object> {
+                            <dyn #object as typetag::__private::Registered>::registry()
+                        }
+
+                        #forward_runtime
+                    }
+                });
+
+                if runtime {
+                    expanded.extend(quote! {
+                        impl<TypetagT> typetag::__private::Construct<TypetagT> for dyn #object + #marker_traits
+                        where
+                            dyn #object: typetag::__private::Construct<TypetagT>,
+                        {
+                            fn deserialize_fn() -> typetag::__private::DeserializeFn<Self::Object> {
+                                <dyn #object as typetag::__private::Construct<TypetagT>>::deserialize_fn()
+                            }
+
+                            fn id() -> typetag::__private::Option<u64> {
+                                <dyn #object as typetag::__private::Construct<TypetagT>>::id()
+                            }
+                        }
+                    });
                 }
             }
-        });
 
-        for marker_traits in others {
-            expanded.extend(quote! {
-                #[allow(unknown_lints, non_local_definitions)] // false positive: https://github.com/rust-lang/rust/issues/121621
-                impl<'de> typetag::__private::serde::Deserialize<'de> for typetag::__private::Box<dyn #object + #marker_traits> {
-                    fn deserialize<D>(deserializer: D) -> typetag::__private::Result<Self, D::Error>
-                    where
-                        D: typetag::__private::serde::Deserializer<'de>,
-                    {
-                        typetag::__private::Result::Ok(
-                            <typetag::__private::Box<dyn #object + #strictest>
-                                as typetag::__private::serde::Deserialize<'de>>::deserialize(deserializer)?
-                        )
+            for marker_traits in &others {
+                if args.seed.is_some() {
+                    expanded.extend(quote! {
+                        impl<'typetag> typetag::__private::Contextual<'typetag> for dyn #object + #marker_traits {
+                            type Context = typetag::__private::Context<'typetag, dyn #object + #strictest>;
+                        }
+
+                        impl typetag::__private::Seeded for dyn #object + #marker_traits {
+                            fn deserialize_seeded<'de, 'typetag, D>(context: &'typetag typetag::__private::Context<'typetag, Self>, deserializer: D) -> typetag::__private::Result<typetag::__private::Box<Self>, D::Error>
+                            where
+                                D: typetag::__private::serde::Deserializer<'de>,
+                            {
+                                typetag::__private::Result::Ok(
+                                    <dyn #object + #strictest as typetag::__private::Seeded>::deserialize_seeded(context, deserializer)?
+                                )
+                            }
+                        }
+                    });
+                    continue;
+                }
+
+                expanded.extend(quote! {
+                    #[allow(unknown_lints, non_local_definitions)] // false positive: https://github.com/rust-lang/rust/issues/121621
+                    impl<'de> typetag::__private::serde::Deserialize<'de> for typetag::__private::Box<dyn #deserialized + #marker_traits> {
+                        fn deserialize<D>(deserializer: D) -> typetag::__private::Result<Self, D::Error>
+                        where
+                            D: typetag::__private::serde::Deserializer<'de>,
+                        {
+                            typetag::__private::Result::Ok(
+                                <typetag::__private::Box<dyn #deserialized + #strictest>
+                                    as typetag::__private::serde::Deserialize<'de>>::deserialize(deserializer)?
+                            )
+                        }
                     }
+                });
+            }
+
+            // Every trait object that deserializes shares the schema of the
+            // registry, under one name so that it is defined once.
+            if args.schema {
+                let name = object.to_string().replace(' ', "");
+                for marker_traits in others.iter().chain(Some(&strictest)) {
+                    let object = if marker_traits.is_empty() {
+                        quote!(#object)
+                    } else {
+                        quote!(#object + #marker_traits)
+                    };
+                    expanded.extend(quote! {
+                        impl typetag::__private::schemars::JsonSchema for dyn #object {
+                            fn schema_name() -> typetag::__private::Cow<'static, str> {
+                                typetag::__private::Cow::Borrowed(#name)
+                            }
+
+                            fn schema_id() -> typetag::__private::Cow<'static, str> {
+                                typetag::__private::Cow::Borrowed(typetag::__private::concat!(
+                                    typetag::__private::module_path!(),
+                                    "::",
+                                    #name,
+                                ))
+                            }
+
+                            fn json_schema(generator: &mut typetag::__private::schemars::SchemaGenerator) -> typetag::__private::schemars::Schema {
+                                let registry = <dyn #object as typetag::__private::Registered>::registry();
+                                #schema_impl
+                            }
+                        }
+                    });
                 }
-            });
+            }
         }
     }
 
@@ -124,16 +447,72 @@ pub(crate) fn expand(args: TraitArgs, mut input: ItemTrait, mode: Mode) -> Token
     }
 }
 
+// Impl of a std trait for the trait object, through the tag and serialized
+// content of the impl behind it.
+fn derived_impl(
+    derive: Derive,
+    object: &TokenStream,
+    tagged_trait: &TokenStream,
+    generics: &Generics,
+    where_clause: Option<&WhereClause>,
+) -> TokenStream {
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let bounds = object;
+    let object = quote!(dyn #bounds + 'typetag);
+    let name = quote!(<Self as #tagged_trait>::typetag_name);
+    match derive {
+        Derive::Debug => quote! {
+            impl #impl_generics typetag::__private::fmt::Debug for #object #where_clause {
+                fn fmt(&self, formatter: &mut typetag::__private::fmt::Formatter) -> typetag::__private::fmt::Result {
+                    typetag::__private::structural::debug(#name(self), self, formatter)
+                }
+            }
+        },
+        Derive::PartialEq => quote! {
+            impl #impl_generics typetag::__private::PartialEq for #object #where_clause {
+                fn eq(&self, other: &Self) -> bool {
+                    typetag::__private::structural::eq(#name(self), self, #name(other), other)
+                }
+            }
+        },
+        Derive::Eq => quote! {
+            impl #impl_generics typetag::__private::Eq for #object #where_clause {}
+        },
+        Derive::Hash => quote! {
+            impl #impl_generics typetag::__private::Hash for #object #where_clause {
+                fn hash<H>(&self, state: &mut H)
+                where
+                    H: typetag::__private::Hasher,
+                {
+                    typetag::__private::structural::hash(#name(self), self, state);
+                }
+            }
+        },
+    }
+}
+
 fn augment_trait(input: &mut ItemTrait, mode: Mode) {
     if mode.ser {
         input.supertraits.push(parse_quote!(typetag::Serialize));
 
         input.items.push(parse_quote! {
             #[doc(hidden)]
-            fn typetag_name(&self) -> &'static str;
+            fn typetag_name(&self) -> typetag::__private::Option<&'static str>;
         });
     }
 
+    // Id of the impl tagged `name`. Overridden by impls that give an explicit
+    // id, which are rejected unless the trait is tagged by id.
+    if mode.ser {
+        let typetag_id = parse_quote! {
+            #[doc(hidden)]
+            fn typetag_id(&self, name: &'static str) -> u64 {
+                typetag::__private::hash_id(name)
+            }
+        };
+        input.items.push(typetag_id);
+    }
+
     if mode.de {
         input.supertraits.push(parse_quote!(typetag::Deserialize));
 
@@ -145,67 +524,658 @@ fn augment_trait(input: &mut ItemTrait, mode: Mode) {
     }
 }
 
-fn build_registry(input: &ItemTrait) -> TokenStream {
+// Impls without a name look up how the trait derives their tag from the type
+// name, and impls with an explicit id whether the trait is tagged by id.
+fn object_consts(
+    input: &ItemTrait,
+    assoc_types: &[AssocType],
+    rule: RenameRule,
+    strategy: NameStrategy,
+    tag_kind: TagKind,
+) -> TokenStream {
     let vis = &input.vis;
-    let object = &input.ident;
+    let registered = trait_object(input, assoc_types.iter());
+    let mut generics = input.generics.clone();
+    for assoc in assoc_types {
+        generics.params.push(assoc.generic_param());
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let rule = rule.ident();
+    let path = match strategy {
+        NameStrategy::Type => false,
+        NameStrategy::Path => true,
+    };
+    let tagged_by_id = tag_kind == TagKind::Id;
 
     quote! {
-        type TypetagStrictest = <dyn #object as typetag::__private::Strictest>::Object;
-        type TypetagFn = typetag::__private::DeserializeFn<TypetagStrictest>;
+        #[allow(unknown_lints, unused_associated_type_bounds)]
+        impl #impl_generics dyn #registered #where_clause {
+            #[doc(hidden)]
+            #vis const TYPETAG_DEFAULT_NAME: typetag::__private::DefaultName =
+                typetag::__private::DefaultName {
+                    rename_all: typetag::__private::RenameRule::#rule,
+                    path: #path,
+                };
 
+            #[doc(hidden)]
+            #vis const TYPETAG_TAGGED_BY_ID: bool = #tagged_by_id;
+        }
+    }
+}
+
+// Impls for the trait object of every instantiation of a generic trait, by
+// binding its generic parameters and associated types to generic parameters.
+fn instantiations(
+    input: &ItemTrait,
+    assoc_types: &[AssocType],
+    others: &[TokenStream],
+    strictest: &TokenStream,
+    seed: Option<&Type>,
+    schema: bool,
+) -> TokenStream {
+    let object = trait_object(input, assoc_types.iter().filter(|assoc| !assoc.sized));
+    let mut generics = input.generics.clone();
+    for assoc in assoc_types.iter().filter(|assoc| !assoc.sized) {
+        generics.params.push(assoc.generic_param());
+    }
+
+    // The trait objects are 'static, so everything they are generic over is.
+    let static_bounds: Vec<WherePredicate> = generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                Some(parse_quote!(#lifetime: 'static))
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                Some(parse_quote!(#ident: 'static))
+            }
+            GenericParam::Const(_) => None,
+        })
+        .collect();
+    generics
+        .make_where_clause()
+        .predicates
+        .extend(static_bounds);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    let mut lifetime_generics = generics.clone();
+    lifetime_generics.params.insert(0, parse_quote!('de));
+    let (lifetime_impl_generics, _, _) = lifetime_generics.split_for_impl();
+
+    let mut context_generics = generics.clone();
+    context_generics.params.insert(0, parse_quote!('typetag));
+    let (context_impl_generics, _, _) = context_generics.split_for_impl();
+    let context = context_type(seed);
+
+    let mut expanded = quote! {
+        impl #impl_generics typetag::__private::Strictest for dyn #object #where_clause {
+            type Object = dyn #object + #strictest;
+        }
+
+        impl #lifetime_impl_generics typetag::__private::Lifetime<'de> for dyn #object + #strictest #where_clause {
+            type Object = dyn #object + #strictest;
+        }
+
+        impl #context_impl_generics typetag::__private::Contextual<'typetag> for dyn #object + #strictest #where_clause {
+            type Context = #context;
+        }
+    };
+
+    for marker_traits in others.iter().chain(Some(strictest)) {
+        if marker_traits.is_empty() {
+            continue;
+        }
+        expanded.extend(quote! {
+            impl #impl_generics typetag::__private::Strictest for dyn #object + #marker_traits #where_clause {
+                type Object = dyn #object + #strictest;
+            }
+        });
+    }
+
+    if !cfg!(feature = "runtime-registration") {
+        let construct_bounds = construct_bounds(strictest, seed, schema);
+        let deserialize_fn = deserialize_fn(seed, schema);
+        let mut construct_generics = generics.clone();
+        construct_generics.params.push(parse_quote!(TypetagT));
+        construct_generics
+            .make_where_clause()
+            .predicates
+            .push(parse_quote!(TypetagT: #object #construct_bounds));
+        let (impl_generics, _, where_clause) = construct_generics.split_for_impl();
+        expanded.extend(quote! {
+            impl #impl_generics typetag::__private::Construct<TypetagT> for dyn #object #where_clause {
+                fn deserialize_fn() -> typetag::__private::DeserializeFn<Self::Object> {
+                    #deserialize_fn
+                }
+            }
+        });
+    }
+
+    expanded
+}
+
+fn build_registry(
+    input: &ItemTrait,
+    assoc_types: &[AssocType],
+    borrow: bool,
+    instantiated: bool,
+) -> TokenStream {
+    let vis = &input.vis;
+    let object = trait_object(input, assoc_types.iter().filter(|assoc| !assoc.sized));
+
+    // Impls name their trait object by binding every associated type they
+    // define, including those bounded by `Self: Sized`, which do not take part
+    // in the trait object that gets deserialized.
+    let registered = trait_object(input, assoc_types.iter());
+    let mut generics = input.generics.clone();
+    for assoc in assoc_types {
+        generics.params.push(assoc.generic_param());
+    }
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+
+    if cfg!(feature = "runtime-registration") {
+        return TokenStream::new();
+    }
+
+    let registration = quote! {
         #vis struct TypetagRegistration<T> {
             name: &'static str,
+            aliases: &'static [&'static str],
+            type_name: fn() -> &'static str,
+            module_path: &'static str,
+            is_override: bool,
+            id: u64,
             deserializer: T,
         }
+    };
+
+    // Impls that borrow from the input bring their own deserialize function,
+    // generic over the lifetime of the input.
+    if borrow {
+        let ident = &input.ident;
+        return quote! {
+            #registration
+
+            impl dyn #ident<'static> {
+                #[doc(hidden)]
+                #vis const fn typetag_register_borrowed(
+                    name: &'static str,
+                    aliases: &'static [&'static str],
+                    type_name: fn() -> &'static str,
+                    module_path: &'static str,
+                    is_override: bool,
+                    id: u64,
+                    deserializer: fn() -> typetag::__private::DeserializeFn<<Self as typetag::__private::Strictest>::Object>,
+                ) -> TypetagRegistration<fn() -> typetag::__private::DeserializeFn<<Self as typetag::__private::Strictest>::Object>> {
+                    TypetagRegistration {
+                        name,
+                        aliases,
+                        type_name,
+                        module_path,
+                        is_override,
+                        id,
+                        deserializer,
+                    }
+                }
+            }
+        };
+    }
+
+    if instantiated {
+        let shared = shared_registration();
+        return quote! {
+            #registration
 
-        typetag::__private::inventory::collect!(TypetagRegistration<TypetagFn>);
+            typetag::__private::inventory::collect!(#shared);
 
-        impl dyn #object {
+            #[allow(unknown_lints, unused_associated_type_bounds)]
+            impl #impl_generics dyn #registered #where_clause {
+                #[doc(hidden)]
+                #vis const fn typetag_register<TypetagT>(
+                    name: &'static str,
+                    aliases: &'static [&'static str],
+                    type_name: fn() -> &'static str,
+                    module_path: &'static str,
+                    is_override: bool,
+                    id: u64,
+                ) -> #shared
+                where
+                    dyn #object: typetag::__private::Construct<TypetagT>,
+                {
+                    TypetagRegistration {
+                        name,
+                        aliases,
+                        type_name,
+                        module_path,
+                        is_override,
+                        id,
+                        deserializer: <dyn #registered>::typetag_construct::<TypetagT>,
+                    }
+                }
+
+                #[doc(hidden)]
+                #vis fn typetag_construct<TypetagT>(slot: &mut dyn typetag::__private::Any)
+                where
+                    dyn #object: typetag::__private::Construct<TypetagT>,
+                {
+                    if let typetag::__private::Option::Some(slot) = slot.downcast_mut::<
+                        typetag::__private::Option<typetag::__private::DeserializeFn<<dyn #object as typetag::__private::Strictest>::Object>>,
+                    >() {
+                        *slot = typetag::__private::Option::Some(
+                            <dyn #object as typetag::__private::Construct<TypetagT>>::deserialize_fn(),
+                        );
+                    }
+                }
+            }
+        };
+    }
+
+    quote! {
+        #registration
+
+        #[allow(unknown_lints, unused_associated_type_bounds)]
+        impl #impl_generics dyn #registered #where_clause {
             #[doc(hidden)]
-            #vis const fn typetag_register<T>(name: &'static str, deserializer: T) -> TypetagRegistration<T> {
-                TypetagRegistration { name, deserializer }
+            #vis const fn typetag_register<TypetagT>(
+                name: &'static str,
+                aliases: &'static [&'static str],
+                type_name: fn() -> &'static str,
+                module_path: &'static str,
+                is_override: bool,
+                id: u64,
+            ) -> TypetagRegistration<fn() -> typetag::__private::DeserializeFn<<dyn #object as typetag::__private::Strictest>::Object>>
+            where
+                dyn #object: typetag::__private::Construct<TypetagT>,
+            {
+                TypetagRegistration {
+                    name,
+                    aliases,
+                    type_name,
+                    module_path,
+                    is_override,
+                    id,
+                    deserializer: <dyn #object as typetag::__private::Construct<TypetagT>>::deserialize_fn,
+                }
             }
         }
     }
 }
 
-fn static_registry() -> TokenStream {
-    quote! {
-        static TYPETAG: typetag::__private::once_cell::race::OnceBox<typetag::__private::Registry<TypetagStrictest>> = typetag::__private::once_cell::race::OnceBox::new();
-        let registry = TYPETAG.get_or_init(|| {
-            let mut map = typetag::__private::BTreeMap::new();
-            let mut names = typetag::__private::Vec::new();
-            for registered in typetag::__private::inventory::iter::<TypetagRegistration<TypetagFn>> {
-                match map.entry(registered.name) {
-                    typetag::__private::btree_map::Entry::Vacant(entry) => {
-                        entry.insert(typetag::__private::Option::Some(registered.deserializer));
-                    }
-                    typetag::__private::btree_map::Entry::Occupied(mut entry) => {
-                        entry.insert(typetag::__private::Option::None);
+#[allow(clippy::too_many_arguments)]
+fn registered_impl(
+    object: &TokenStream,
+    strictest: &TokenStream,
+    tag_kind: TagKind,
+    seed: Option<&Type>,
+    borrow: bool,
+    schema: bool,
+    instantiated: bool,
+) -> TokenStream {
+    let construct_bounds = construct_bounds(strictest, seed, schema);
+    let deserialize_fn = deserialize_fn(seed, schema);
+
+    if cfg!(feature = "runtime-registration") {
+        let (id_bound, id) = match tag_kind {
+            TagKind::Name => (quote!(), quote!(typetag::__private::Option::None)),
+            TagKind::Id => (
+                quote!(TypetagT: typetag::__private::InstanceId<dyn #object>,),
+                quote!(typetag::__private::Option::Some(
+                    <TypetagT as typetag::__private::InstanceId<dyn #object>>::ID
+                )),
+            ),
+        };
+
+        quote! {
+            impl typetag::__private::Registered for dyn #object {
+                fn registry() -> &'static typetag::__private::Registry<Self::Object> {
+                    <Self as typetag::__private::Registered>::runtime().registry()
+                }
+
+                fn runtime() -> &'static typetag::__private::RuntimeRegistry<Self::Object> {
+                    static TYPETAG: typetag::__private::RuntimeRegistry<<dyn #object as typetag::__private::Strictest>::Object> = typetag::__private::RuntimeRegistry::new();
+                    &TYPETAG
+                }
+            }
+
+            impl<TypetagT> typetag::__private::Construct<TypetagT> for dyn #object
+            where
+                TypetagT: #object #construct_bounds,
+                #id_bound
+            {
+                fn deserialize_fn() -> typetag::__private::DeserializeFn<Self::Object> {
+                    #deserialize_fn
+                }
+
+                fn id() -> typetag::__private::Option<u64> {
+                    #id
+                }
+            }
+        }
+    } else {
+        // Every impl registers an id, which only traits tagged by id use.
+        let id = match tag_kind {
+            TagKind::Name => quote!(typetag::__private::Option::None),
+            TagKind::Id => quote!(typetag::__private::Option::Some(registered.id)),
+        };
+
+        let registration = if instantiated {
+            shared_registration()
+        } else {
+            quote! {
+                TypetagRegistration<fn() -> typetag::__private::DeserializeFn<<dyn #object as typetag::__private::Strictest>::Object>>
+            }
+        };
+
+        // Impls of a generic trait register together, each for whichever
+        // instantiation it implements, and every registry keeps those that
+        // construct its own trait object.
+        let (collect, registered_fn) = if instantiated {
+            (
+                quote!(),
+                quote! {
+                    .filter_map(|registered| {
+                        let mut deserializer: typetag::__private::Option<typetag::__private::DeserializeFn<Self::Object>> = typetag::__private::Option::None;
+                        (registered.deserializer)(&mut deserializer);
+                        typetag::__private::Option::Some(typetag::__private::Registration {
+                            name: registered.name,
+                            aliases: registered.aliases,
+                            deserializer: deserializer?,
+                            type_name: registered.type_name,
+                            module_path: registered.module_path,
+                            is_override: registered.is_override,
+                            id: #id,
+                        })
+                    })
+                },
+            )
+        } else {
+            (
+                quote!(typetag::__private::inventory::collect!(#registration);),
+                quote! {
+                    .map(|registered| typetag::__private::Registration {
+                        name: registered.name,
+                        aliases: registered.aliases,
+                        deserializer: (registered.deserializer)(),
+                        type_name: registered.type_name,
+                        module_path: registered.module_path,
+                        is_override: registered.is_override,
+                        id: #id,
+                    })
+                },
+            )
+        };
+
+        let mut registered = quote! {
+            #collect
+
+            impl typetag::__private::Registered for dyn #object {
+                fn registry() -> &'static typetag::__private::Registry<Self::Object> {
+                    static TYPETAG: typetag::__private::once_cell::race::OnceBox<typetag::__private::Registry<<dyn #object as typetag::__private::Strictest>::Object>> = typetag::__private::once_cell::race::OnceBox::new();
+                    TYPETAG.get_or_init(|| {
+                        let registrations = typetag::__private::inventory::iter::<#registration>;
+                        typetag::__private::Box::new(typetag::__private::Registry::new(
+                            registrations
+                                .into_iter()
+                                #registered_fn,
+                        ))
+                    })
+                }
+            }
+        };
+
+        if !borrow && !instantiated {
+            registered.extend(quote! {
+                impl<TypetagT> typetag::__private::Construct<TypetagT> for dyn #object
+                where
+                    TypetagT: #object #construct_bounds,
+                {
+                    fn deserialize_fn() -> typetag::__private::DeserializeFn<Self::Object> {
+                        #deserialize_fn
                     }
                 }
-                names.push(registered.name);
+            });
+        }
+
+        registered
+    }
+}
+
+// Registration type that the impls of a generic trait submit, with the
+// function that constructs the deserialize function of their trait object
+// into the slot of whichever registry asks for it.
+fn shared_registration() -> TokenStream {
+    quote! {
+        TypetagRegistration<fn(&mut dyn typetag::__private::Any)>
+    }
+}
+
+// The context of the seed of the trait, with every lifetime in it bound to
+// the 'typetag lifetime that the context is borrowed for.
+fn context_type(seed: Option<&Type>) -> TokenStream {
+    match seed {
+        Some(seed) => {
+            let mut context = seed.clone();
+            ContextLifetime.visit_type_mut(&mut context);
+            quote!(#context)
+        }
+        None => quote!(()),
+    }
+}
+
+struct ContextLifetime;
+
+impl VisitMut for ContextLifetime {
+    fn visit_lifetime_mut(&mut self, lifetime: &mut Lifetime) {
+        if lifetime.ident != "static" {
+            *lifetime = Lifetime::new("'typetag", lifetime.span());
+        }
+    }
+}
+
+// Bounds on an impl for the trait object to construct its deserialize
+// function, other than the trait itself.
+fn construct_bounds(strictest: &TokenStream, seed: Option<&Type>, schema: bool) -> TokenStream {
+    let strictest_bound = if strictest.is_empty() {
+        quote!()
+    } else {
+        quote!(+ #strictest)
+    };
+    let deserialize_bound = match seed {
+        Some(_) => {
+            let context = context_type(seed);
+            quote!(for<'typetag> typetag::DeserializeWith<'typetag, #context>)
+        }
+        None => quote!(typetag::__private::serde::de::DeserializeOwned),
+    };
+    let schema_bound = if schema {
+        quote!(+ typetag::__private::schemars::JsonSchema)
+    } else {
+        quote!()
+    };
+    quote!(#strictest_bound + #deserialize_bound #schema_bound + 'static)
+}
+
+// Deserialization of the impl `TypetagT` into a Box, from the seed it builds
+// out of the context if the trait has one, along with the schema of its input
+// if the trait is declared with `schema`.
+fn deserialize_fn(seed: Option<&Type>, schema: bool) -> TokenStream {
+    let (context, deserialize) = match seed {
+        Some(_) => (
+            quote!(context),
+            quote! {
+                let seed = <TypetagT as typetag::DeserializeWith<_>>::seed(context);
+                typetag::__private::serde::de::DeserializeSeed::deserialize(seed, deserializer)?
+            },
+        ),
+        None => (
+            quote!(_),
+            quote!(typetag::__private::erased_serde::deserialize::<TypetagT>(
+                deserializer
+            )?),
+        ),
+    };
+    let schema = if !cfg!(feature = "schemars") {
+        quote!()
+    } else if schema {
+        quote! {
+            schema: typetag::__private::Option::Some(|generator| {
+                generator.subschema_for::<TypetagT>()
+            }),
+        }
+    } else {
+        quote!(schema: typetag::__private::Option::None,)
+    };
+    quote! {
+        typetag::__private::DeserializeFn {
+            boxed: |#context, deserializer| typetag::__private::Result::Ok(
+                typetag::__private::Box::new({
+                    #deserialize
+                }),
+            ),
+            #schema
+        }
+    }
+}
+
+// Body of the JsonSchema impl, with the schema of every registered impl in
+// the place where the representation puts its data.
+fn schema_impl(repr: &Repr) -> TokenStream {
+    match repr {
+        Repr::External | Repr::Variant => quote! {
+            typetag::__private::schema::externally(generator, registry)
+        },
+        Repr::Untagged => quote! {
+            typetag::__private::schema::untagged(generator, registry)
+        },
+        Repr::Internal {
+            tag,
+            value_key,
+            default_variant,
+        } => {
+            let value_key = match value_key {
+                Some(value_key) => value_key.value(),
+                None => "value".to_owned(),
+            };
+            let default_variant = match default_variant {
+                Some(variant) => quote!(typetag::__private::Option::Some(#variant)),
+                None => quote!(typetag::__private::Option::None),
+            };
+            quote! {
+                typetag::__private::schema::internally(generator, registry, #tag, #value_key, #default_variant)
             }
-            names.sort_unstable();
-            typetag::__private::Box::new(typetag::__private::Registry { map, names })
-        });
+        }
+        Repr::Adjacent {
+            tag,
+            content,
+            default_variant,
+            ..
+        } => {
+            let default_variant = match default_variant {
+                Some(variant) => quote!(typetag::__private::Option::Some(#variant)),
+                None => quote!(typetag::__private::Option::None),
+            };
+            quote! {
+                typetag::__private::schema::adjacently(generator, registry, &[#tag, #content], #default_variant)
+            }
+        }
     }
 }
 
-fn externally_tagged(input: &ItemTrait) -> (TokenStream, TokenStream) {
+// Expression for the numeric id of `self` in the generated Serialize impl.
+fn serialized_id(input: &ItemTrait, tag_kind: TagKind) -> TokenStream {
+    match tag_kind {
+        TagKind::Name => quote!(typetag::__private::Option::None),
+        TagKind::Id => {
+            let object = &input.ident;
+            let (_, ty_generics, _) = input.generics.split_for_impl();
+            quote! {
+                typetag::__private::Option::Some(<Self as #object #ty_generics>::typetag_id(self, name))
+            }
+        }
+    }
+}
+
+fn externally_tagged(id: &TokenStream, input: &ItemTrait) -> (TokenStream, TokenStream) {
+    let object_name = input.ident.to_string();
+
+    let serialize_impl = quote! {
+        typetag::__private::externally::serialize(serializer, name, #id, self)
+    };
+
+    let deserialize_impl = quote! {
+        typetag::__private::externally::deserialize(deserializer, #object_name, registry, context)
+    };
+
+    (serialize_impl, deserialize_impl)
+}
+
+fn untagged(input: &ItemTrait) -> (TokenStream, TokenStream) {
+    let object_name = input.ident.to_string();
+
+    // The tag is left out, though an impl without one still fails to
+    // serialize, since it would not deserialize.
+    let serialize_impl = quote! {
+        let _ = name;
+        typetag::__private::untagged::serialize(serializer, self)
+    };
+
+    let deserialize_impl = quote! {
+        typetag::__private::untagged::deserialize(deserializer, #object_name, registry, context)
+    };
+
+    (serialize_impl, deserialize_impl)
+}
+
+fn variant(input: &ItemTrait) -> (TokenStream, TokenStream) {
     let object = &input.ident;
     let object_name = object.to_string();
     let (_, ty_generics, _) = input.generics.split_for_impl();
-    let static_registry = static_registry();
 
     let serialize_impl = quote! {
-        let name = <Self as #object #ty_generics>::typetag_name(self);
-        typetag::__private::externally::serialize(serializer, name, self)
+        let id = <Self as #object #ty_generics>::typetag_id(self, name);
+        typetag::__private::variant::serialize(serializer, #object_name, name, id, self)
+    };
+
+    let deserialize_impl = quote! {
+        typetag::__private::variant::deserialize(deserializer, #object_name, registry, context)
+    };
+
+    (serialize_impl, deserialize_impl)
+}
+
+// Data of unknown tags goes to and comes back from the impl registered as
+// `unknown` unchanged, around the usual representation.
+fn unknown_variant(
+    unknown: &LitStr,
+    tagging: &TokenStream,
+    serialize_impl: TokenStream,
+    deserialize_impl: TokenStream,
+    input: &ItemTrait,
+) -> (TokenStream, TokenStream) {
+    let object_name = input.ident.to_string();
+
+    let serialize_impl = quote! {
+        if name == #unknown {
+            return typetag::__private::untagged::serialize(serializer, self);
+        }
+        #serialize_impl
     };
 
     let deserialize_impl = quote! {
-        #static_registry
-        typetag::__private::externally::deserialize(deserializer, #object_name, registry)
+        typetag::__private::unknown::deserialize(
+            deserializer,
+            #object_name,
+            typetag::__private::unknown::Tagging::#tagging,
+            registry,
+            context,
+            #unknown,
+            |deserializer| {
+                #deserialize_impl
+            },
+        )
     };
 
     (serialize_impl, deserialize_impl)
@@ -213,26 +1183,27 @@ fn externally_tagged(input: &ItemTrait) -> (TokenStream, TokenStream) {
 
 fn internally_tagged(
     tag: LitStr,
+    value_key: Option<LitStr>,
     default_variant: Option<LitStr>,
+    id: &TokenStream,
     input: &ItemTrait,
 ) -> (TokenStream, TokenStream) {
-    let object = &input.ident;
-    let object_name = object.to_string();
-    let (_, ty_generics, _) = input.generics.split_for_impl();
-    let static_registry = static_registry();
+    let object_name = input.ident.to_string();
     let default_variant_literal = match default_variant {
         Some(variant) => quote!(typetag::__private::Option::Some(#variant)),
         None => quote!(typetag::__private::Option::None),
     };
+    let value_key = match value_key {
+        Some(value_key) => value_key.value(),
+        None => "value".to_owned(),
+    };
 
     let serialize_impl = quote! {
-        let name = <Self as #object #ty_generics>::typetag_name(self);
-        typetag::__private::internally::serialize(serializer, #tag, name, self)
+        typetag::__private::internally::serialize(serializer, #tag, name, #id, #value_key, self)
     };
 
     let deserialize_impl = quote! {
-        #static_registry
-        typetag::__private::internally::deserialize(deserializer, #object_name, #tag, #default_variant_literal, registry)
+        typetag::__private::internally::deserialize(deserializer, #object_name, #tag, #value_key, #default_variant_literal, registry, context)
     };
 
     (serialize_impl, deserialize_impl)
@@ -243,30 +1214,27 @@ fn adjacently_tagged(
     content: LitStr,
     default_variant: Option<LitStr>,
     deny_unknown_fields: bool,
+    id: &TokenStream,
     input: &ItemTrait,
 ) -> (TokenStream, TokenStream) {
-    let object = &input.ident;
-    let object_name = object.to_string();
-    let (_, ty_generics, _) = input.generics.split_for_impl();
-    let static_registry = static_registry();
+    let object_name = input.ident.to_string();
     let default_variant_literal = match default_variant {
         Some(variant) => quote!(typetag::__private::Option::Some(#variant)),
         None => quote!(typetag::__private::Option::None),
     };
 
     let serialize_impl = quote! {
-        let name = <Self as #object #ty_generics>::typetag_name(self);
-        typetag::__private::adjacently::serialize(serializer, #object_name, #tag, name, #content, self)
+        typetag::__private::adjacently::serialize(serializer, #object_name, #tag, name, #id, #content, self)
     };
 
     let deserialize_impl = quote! {
-        #static_registry
         typetag::__private::adjacently::deserialize(
             deserializer,
             #object_name,
             &[#tag, #content],
             #default_variant_literal,
             registry,
+            context,
             #deny_unknown_fields,
         )
     };
@@ -274,15 +1242,166 @@ fn adjacently_tagged(
     (serialize_impl, deserialize_impl)
 }
 
-fn has_supertrait(input: &ItemTrait, find: &str) -> bool {
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Marker {
+    Send,
+    Sync,
+    Unpin,
+    UnwindSafe,
+    RefUnwindSafe,
+}
+
+impl Marker {
+    // Name of each marker and the module of core that defines it.
+    const ALL: [(&'static str, &'static str, Marker); 5] = [
+        ("Send", "marker", Marker::Send),
+        ("Sync", "marker", Marker::Sync),
+        ("Unpin", "marker", Marker::Unpin),
+        ("UnwindSafe", "panic", Marker::UnwindSafe),
+        ("RefUnwindSafe", "panic", Marker::RefUnwindSafe),
+    ];
+
+    fn path(self) -> TokenStream {
+        match self {
+            Marker::Send => quote!(typetag::__private::Send),
+            Marker::Sync => quote!(typetag::__private::Sync),
+            Marker::Unpin => quote!(typetag::__private::Unpin),
+            Marker::UnwindSafe => quote!(typetag::__private::UnwindSafe),
+            Marker::RefUnwindSafe => quote!(typetag::__private::RefUnwindSafe),
+        }
+    }
+}
+
+// Auto traits among the supertraits, written either bare as in `Send` or by
+// their path in core or std as in `core::marker::Send`.
+fn marker_supertraits(input: &ItemTrait) -> Vec<Marker> {
+    let mut markers = Vec::new();
     for supertrait in &input.supertraits {
         if let TypeParamBound::Trait(trait_bound) = supertrait {
             if let TraitBoundModifier::None = trait_bound.modifier {
-                if trait_bound.path.is_ident(find) {
-                    return true;
+                if let Some(marker) = marker(&trait_bound.path) {
+                    markers.push(marker);
                 }
             }
         }
     }
-    false
+    markers.sort_unstable();
+    markers.dedup();
+    markers
+}
+
+fn marker(path: &Path) -> Option<Marker> {
+    if path
+        .segments
+        .iter()
+        .any(|segment| !segment.arguments.is_empty())
+    {
+        return None;
+    }
+    let segments: Vec<String> = path
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect();
+    Marker::ALL
+        .iter()
+        .find(|(name, module, _marker)| match segments.as_slice() {
+            [ident] => path.leading_colon.is_none() && ident == name,
+            [krate, m, ident] => {
+                (krate == "core" || krate == "std") && m == module && ident == name
+            }
+            _ => false,
+        })
+        .map(|(_name, _module, marker)| *marker)
+}
+
+// Every combination of the markers as bounds of a trait object, from none of
+// them first to all of them last.
+fn marker_subsets(markers: &[Marker]) -> Vec<TokenStream> {
+    let mut subsets: Vec<Vec<Marker>> = (0..1usize << markers.len())
+        .map(|bits| {
+            markers
+                .iter()
+                .enumerate()
+                .filter(|(i, _marker)| bits & (1 << i) != 0)
+                .map(|(_i, marker)| *marker)
+                .collect()
+        })
+        .collect();
+    subsets.sort_by_key(Vec::len);
+    subsets
+        .iter()
+        .map(|subset| {
+            let paths = subset.iter().map(|marker| marker.path());
+            quote!(#(#paths)+*)
+        })
+        .collect()
+}
+
+struct AssocType {
+    ident: Ident,
+    param: Ident,
+    bounds: Punctuated<TypeParamBound, Token![+]>,
+    sized: bool,
+}
+
+impl AssocType {
+    fn generic_param(&self) -> GenericParam {
+        let param = &self.param;
+        let bounds = &self.bounds;
+        parse_quote!(#param: #bounds)
+    }
+}
+
+fn associated_types(input: &ItemTrait) -> Vec<AssocType> {
+    let mut assoc_types = Vec::new();
+    for item in &input.items {
+        if let TraitItem::Type(assoc) = item {
+            if assoc.generics.params.is_empty() {
+                assoc_types.push(AssocType {
+                    ident: assoc.ident.clone(),
+                    param: format_ident!("Typetag{}", assoc.ident),
+                    bounds: assoc.bounds.clone(),
+                    sized: parse::is_self_sized(&assoc.generics),
+                });
+            }
+        }
+    }
+    assoc_types
+}
+
+// The trait with its own generic parameters and the given associated types
+// bound to generic parameters, as in `Trait<T, Assoc = TypetagAssoc>`.
+fn trait_object<'a>(
+    input: &ItemTrait,
+    assoc_types: impl Iterator<Item = &'a AssocType>,
+) -> TokenStream {
+    let ident = &input.ident;
+    let mut args = Vec::new();
+    for param in &input.generics.params {
+        args.push(match param {
+            GenericParam::Lifetime(param) => {
+                let lifetime = &param.lifetime;
+                quote!(#lifetime)
+            }
+            GenericParam::Type(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+            GenericParam::Const(param) => {
+                let ident = &param.ident;
+                quote!(#ident)
+            }
+        });
+    }
+    for assoc in assoc_types {
+        let ident = &assoc.ident;
+        let param = &assoc.param;
+        args.push(quote!(#ident = #param));
+    }
+    if args.is_empty() {
+        quote!(#ident)
+    } else {
+        quote!(#ident<#(#args),*>)
+    }
 }
@@ -1,7 +1,7 @@
 use crate::content::Content;
 use crate::de::{FnApply, MapLookupVisitor};
-use crate::private::Registry;
-use crate::ser::Wrap;
+use crate::private::{Context, Family, Object, Registry};
+use crate::ser::{Variant, Wrap};
 use alloc::boxed::Box;
 use core::fmt;
 use serde::de::{
@@ -15,6 +15,7 @@ pub fn serialize<S, T>(
     trait_object: &'static str,
     tag: &'static str,
     variant: &'static str,
+    id: Option<u64>,
     content: &'static str,
     concrete: &T,
 ) -> Result<S::Ok, S::Error>
@@ -22,23 +23,25 @@ where
     S: Serializer,
     T: ?Sized + erased_serde::Serialize,
 {
+    let variant = Variant::new(variant, id, serializer.is_human_readable());
     let mut ser = serializer.serialize_struct(trait_object, 2)?;
-    ser.serialize_field(tag, variant)?;
+    ser.serialize_field(tag, &variant)?;
     ser.serialize_field(content, &Wrap(concrete))?;
     ser.end()
 }
 
-pub fn deserialize<'de, D, T>(
+pub fn deserialize<'de, 'c, D, T>(
     deserializer: D,
     trait_object: &'static str,
     field_names: &'static [&'static str; 2], // [tag, content]
     default_variant: Option<&'static str>,
     registry: &'static Registry<T>,
+    context: &'c Context<'c, T>,
     deny_unknown_fields: bool,
-) -> Result<Box<T>, D::Error>
+) -> Result<Box<Object<'de, T>>, D::Error>
 where
     D: Deserializer<'de>,
-    T: ?Sized,
+    T: ?Sized + Family,
 {
     let visitor = TaggedVisitor {
         trait_object,
@@ -46,20 +49,22 @@ where
         default_variant,
         registry,
         deny_unknown_fields,
+        context,
     };
     deserializer.deserialize_struct(trait_object, field_names, visitor)
 }
 
-struct TaggedVisitor<T: ?Sized + 'static> {
+struct TaggedVisitor<'c, T: ?Sized + Family + 'static> {
     trait_object: &'static str,
     field_names: &'static [&'static str; 2], // [tag, content]
     default_variant: Option<&'static str>,
     registry: &'static Registry<T>,
     deny_unknown_fields: bool,
+    context: &'c Context<'c, T>,
 }
 
-impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
-    type Value = Box<T>;
+impl<'de, 'c, T: ?Sized + Family> Visitor<'de> for TaggedVisitor<'c, T> {
+    type Value = Box<Object<'de, T>>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "dyn {}", self.trait_object)
@@ -108,12 +113,12 @@ impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
                     }
                     // Second key is the content.
                     Some(TagOrContentField::Content) => {
-                        let fn_apply = FnApply { deserialize_fn };
+                        let fn_apply = FnApply::new(deserialize_fn, self.context);
                         map.next_value_seed(fn_apply)?
                     }
                     // There is no second key; might be okay if the we have a unit variant.
                     None => {
-                        let fn_apply = FnApply { deserialize_fn };
+                        let fn_apply = FnApply::new(deserialize_fn, self.context);
                         let unit = ().into_deserializer();
                         return fn_apply.deserialize(unit);
                     }
@@ -129,7 +134,7 @@ impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
                     Some(TagOrContentField::Tag) => {
                         // Parse the tag.
                         let deserialize_fn = map.next_value_seed(map_lookup)?;
-                        let fn_apply = FnApply { deserialize_fn };
+                        let fn_apply = FnApply::new(deserialize_fn, self.context);
                         let content = content.into_deserializer();
                         fn_apply.deserialize(content)?
                     }
@@ -141,7 +146,7 @@ impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
                     None => {
                         if let Some(variant) = self.default_variant {
                             let deserialize_fn = map_lookup.visit_str(variant)?;
-                            let fn_apply = FnApply { deserialize_fn };
+                            let fn_apply = FnApply::new(deserialize_fn, self.context);
                             let content = content.into_deserializer();
                             fn_apply.deserialize(content)?
                         } else {
@@ -185,7 +190,7 @@ impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
         };
 
         // Visit the second element - the content.
-        let fn_apply = FnApply { deserialize_fn };
+        let fn_apply = FnApply::new(deserialize_fn, self.context);
         match seq.next_element_seed(fn_apply)? {
             Some(ret) => Ok(ret),
             None => Err(de::Error::invalid_length(1, &self)),
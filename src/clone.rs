@@ -0,0 +1,146 @@
+use crate::content::Content;
+use crate::ser::{self, ContentSerializer};
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use serde::de::{DeserializeOwned, Error as _, IntoDeserializer};
+use serde::Serialize;
+
+/// Deep clone of a trait object, by serializing it and deserializing the
+/// result back through the registry of the trait.
+///
+/// The value goes through typetag's own in-memory buffer rather than any data
+/// format, so this works for every trait declared with `#[typetag::serde]`
+/// without a serializer crate in the dependencies. It is a convenient way to
+/// implement `Clone` for `Box<dyn Trait>`.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[typetag::serde]
+/// trait Shape {
+///     fn area(&self) -> f64;
+/// }
+///
+/// impl Clone for Box<dyn Shape> {
+///     fn clone(&self) -> Self {
+///         typetag::clone_box(&**self)
+///     }
+/// }
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Circle {
+///     radius: f64,
+/// }
+///
+/// #[typetag::serde]
+/// impl Shape for Circle {
+///     fn area(&self) -> f64 {
+///         3.0 * self.radius * self.radius
+///     }
+/// }
+/// #
+/// # #[cfg(feature = "runtime-registration")]
+/// # typetag::register::<dyn Shape, Circle>("Circle");
+///
+/// let shape: Box<dyn Shape> = Box::new(Circle { radius: 2.0 });
+/// let copy = shape.clone();
+/// assert_eq!(copy.area(), 12.0);
+/// ```
+///
+/// # Panics
+///
+/// Panics if the value does not deserialize back from what it serialized
+/// into, for example because its impl is not registered, its tag is
+/// ambiguous, or it holds a 128-bit integer, which the buffer does not
+/// support.
+pub fn clone_box<T>(value: &T) -> Box<T>
+where
+    T: ?Sized + Serialize,
+    Box<T>: DeserializeOwned,
+{
+    match try_clone_box(value) {
+        Ok(clone) => clone,
+        Err(error) => panic!("failed to clone {}: {}", core::any::type_name::<T>(), error),
+    }
+}
+
+fn try_clone_box<T>(value: &T) -> Result<Box<T>, erased_serde::Error>
+where
+    T: ?Sized + Serialize,
+    Box<T>: DeserializeOwned,
+{
+    let content = value.serialize(ContentSerializer::<erased_serde::Error>::new())?;
+    let content = deserializable(content)?;
+    <Box<T> as serde::Deserialize>::deserialize(content.into_deserializer())
+}
+
+// The serialized content in the shape that the self-describing formats hand to
+// Deserialize: structs as maps, tuples as sequences, and enums as a string or
+// a map with a single key.
+fn deserializable(content: ser::Content) -> Result<Content<'static>, erased_serde::Error> {
+    Ok(match content {
+        ser::Content::Bool(v) => Content::Bool(v),
+        ser::Content::U8(v) => Content::U8(v),
+        ser::Content::U16(v) => Content::U16(v),
+        ser::Content::U32(v) => Content::U32(v),
+        ser::Content::U64(v) => Content::U64(v),
+        ser::Content::U128(v) => match u64::try_from(v) {
+            Ok(v) => Content::U64(v),
+            Err(_) => return Err(erased_serde::Error::custom("u128 is not supported")),
+        },
+        ser::Content::I8(v) => Content::I8(v),
+        ser::Content::I16(v) => Content::I16(v),
+        ser::Content::I32(v) => Content::I32(v),
+        ser::Content::I64(v) => Content::I64(v),
+        ser::Content::I128(v) => match i64::try_from(v) {
+            Ok(v) => Content::I64(v),
+            Err(_) => return Err(erased_serde::Error::custom("i128 is not supported")),
+        },
+        ser::Content::F32(v) => Content::F32(v),
+        ser::Content::F64(v) => Content::F64(v),
+        ser::Content::Char(v) => Content::Char(v),
+        ser::Content::String(v) => Content::String(v),
+        ser::Content::Bytes(v) => Content::ByteBuf(v),
+        ser::Content::None => Content::None,
+        ser::Content::Some(v) => Content::Some(Box::new(deserializable(*v)?)),
+        ser::Content::Unit | ser::Content::UnitStruct(_) => Content::Unit,
+        ser::Content::UnitVariant(_, _, variant) => Content::Str(variant),
+        ser::Content::NewtypeStruct(_, v) => Content::Newtype(Box::new(deserializable(*v)?)),
+        ser::Content::NewtypeVariant(_, _, variant, v) => {
+            Content::Map(vec![(Content::Str(variant), deserializable(*v)?)])
+        }
+        ser::Content::Seq(elements)
+        | ser::Content::Tuple(elements)
+        | ser::Content::TupleStruct(_, elements) => Content::Seq(seq(elements)?),
+        ser::Content::TupleVariant(_, _, variant, elements) => {
+            Content::Map(vec![(Content::Str(variant), Content::Seq(seq(elements)?))])
+        }
+        ser::Content::Map(entries) => {
+            let mut map = Vec::with_capacity(entries.len());
+            for (key, value) in entries {
+                map.push((deserializable(key)?, deserializable(value)?));
+            }
+            Content::Map(map)
+        }
+        ser::Content::Struct(_, fields) => Content::Map(fields_map(fields)?),
+        ser::Content::StructVariant(_, _, variant, fields) => Content::Map(vec![(
+            Content::Str(variant),
+            Content::Map(fields_map(fields)?),
+        )]),
+    })
+}
+
+fn seq(elements: Vec<ser::Content>) -> Result<Vec<Content<'static>>, erased_serde::Error> {
+    elements.into_iter().map(deserializable).collect()
+}
+
+fn fields_map(
+    fields: Vec<(&'static str, ser::Content)>,
+) -> Result<Vec<(Content<'static>, Content<'static>)>, erased_serde::Error> {
+    fields
+        .into_iter()
+        .map(|(key, value)| Ok((Content::Str(key), deserializable(value)?)))
+        .collect()
+}
@@ -9,10 +9,12 @@ use serde::de::{
     IntoDeserializer, MapAccess, SeqAccess, Unexpected, VariantAccess, Visitor,
 };
 use serde::forward_to_deserialize_any;
+use serde::ser::{Serialize, Serializer};
 
 // Used from generated code to buffer the contents of the Deserializer when
-// deserializing internally and adjacently tagged trait objects.
-#[derive(Debug)]
+// deserializing internally, adjacently, and untagged trait objects, and to keep
+// the data of unknown tags.
+#[derive(Clone, Debug)]
 pub enum Content<'de> {
     Bool(bool),
 
@@ -45,7 +47,7 @@ pub enum Content<'de> {
 }
 
 impl<'de> Content<'de> {
-    fn unexpected(&self) -> Unexpected {
+    fn unexpected(&self) -> Unexpected<'_> {
         match *self {
             Content::Bool(b) => Unexpected::Bool(b),
             Content::U8(n) => Unexpected::Unsigned(u64::from(n)),
@@ -70,6 +72,87 @@ impl<'de> Content<'de> {
             Content::Map(_) => Unexpected::Map,
         }
     }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Content::String(ref s) => Some(s),
+            Content::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Content::U8(n) => Some(u64::from(n)),
+            Content::U16(n) => Some(u64::from(n)),
+            Content::U32(n) => Some(u64::from(n)),
+            Content::U64(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn into_owned(self) -> Content<'static> {
+        match self {
+            Content::Bool(b) => Content::Bool(b),
+            Content::U8(n) => Content::U8(n),
+            Content::U16(n) => Content::U16(n),
+            Content::U32(n) => Content::U32(n),
+            Content::U64(n) => Content::U64(n),
+            Content::I8(n) => Content::I8(n),
+            Content::I16(n) => Content::I16(n),
+            Content::I32(n) => Content::I32(n),
+            Content::I64(n) => Content::I64(n),
+            Content::F32(f) => Content::F32(f),
+            Content::F64(f) => Content::F64(f),
+            Content::Char(c) => Content::Char(c),
+            Content::String(s) => Content::String(s),
+            Content::Str(s) => Content::String(s.into()),
+            Content::ByteBuf(b) => Content::ByteBuf(b),
+            Content::Bytes(b) => Content::ByteBuf(b.into()),
+            Content::None => Content::None,
+            Content::Some(v) => Content::Some(Box::new(v.into_owned())),
+            Content::Unit => Content::Unit,
+            Content::Newtype(v) => Content::Newtype(Box::new(v.into_owned())),
+            Content::Seq(v) => Content::Seq(v.into_iter().map(Content::into_owned).collect()),
+            Content::Map(v) => Content::Map(
+                v.into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'de> Serialize for Content<'de> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            Content::Bool(b) => serializer.serialize_bool(b),
+            Content::U8(n) => serializer.serialize_u8(n),
+            Content::U16(n) => serializer.serialize_u16(n),
+            Content::U32(n) => serializer.serialize_u32(n),
+            Content::U64(n) => serializer.serialize_u64(n),
+            Content::I8(n) => serializer.serialize_i8(n),
+            Content::I16(n) => serializer.serialize_i16(n),
+            Content::I32(n) => serializer.serialize_i32(n),
+            Content::I64(n) => serializer.serialize_i64(n),
+            Content::F32(f) => serializer.serialize_f32(f),
+            Content::F64(f) => serializer.serialize_f64(f),
+            Content::Char(c) => serializer.serialize_char(c),
+            Content::String(ref s) => serializer.serialize_str(s),
+            Content::Str(s) => serializer.serialize_str(s),
+            Content::ByteBuf(ref b) => serializer.serialize_bytes(b),
+            Content::Bytes(b) => serializer.serialize_bytes(b),
+            Content::None => serializer.serialize_none(),
+            Content::Some(ref v) => serializer.serialize_some(&**v),
+            Content::Unit => serializer.serialize_unit(),
+            Content::Newtype(ref v) => serializer.serialize_newtype_struct("", &**v),
+            Content::Seq(ref v) => serializer.collect_seq(v),
+            Content::Map(ref v) => serializer.collect_map(v.iter().map(|(k, v)| (k, v))),
+        }
+    }
 }
 
 impl<'de> Deserialize<'de> for Content<'de> {
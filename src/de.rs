@@ -1,22 +1,22 @@
-use crate::private::{DeserializeFn, Registry};
+use crate::private::{Context, DeserializeFn, Family, Object, Registry};
 use alloc::boxed::Box;
 use core::fmt;
 use serde::de::{self, DeserializeSeed, Deserializer, Expected, Visitor};
 
-pub struct MapLookupVisitor<'a, T: ?Sized + 'static> {
+pub struct MapLookupVisitor<'a, T: ?Sized + Family + 'static> {
     pub expected: &'a dyn Expected,
     pub registry: &'static Registry<T>,
 }
 
-impl<'a, T: ?Sized + 'static> Copy for MapLookupVisitor<'a, T> {}
+impl<'a, T: ?Sized + Family + 'static> Copy for MapLookupVisitor<'a, T> {}
 
-impl<'a, T: ?Sized + 'static> Clone for MapLookupVisitor<'a, T> {
+impl<'a, T: ?Sized + Family + 'static> Clone for MapLookupVisitor<'a, T> {
     fn clone(&self) -> Self {
         *self
     }
 }
 
-impl<'de, 'a, T: ?Sized + 'static> Visitor<'de> for MapLookupVisitor<'a, T> {
+impl<'de, 'a, T: ?Sized + Family + 'static> Visitor<'de> for MapLookupVisitor<'a, T> {
     type Value = DeserializeFn<T>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
@@ -36,31 +36,64 @@ impl<'de, 'a, T: ?Sized + 'static> Visitor<'de> for MapLookupVisitor<'a, T> {
             None => Err(de::Error::unknown_variant(key, &self.registry.names)),
         }
     }
+
+    fn visit_u64<E>(self, id: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        match self.registry.ids.get(&id) {
+            Some(Some(value)) => Ok(*value),
+            Some(None) => Err(de::Error::custom(format_args!(
+                "non-unique id of {}: {}",
+                self.expected, id
+            ))),
+            None => Err(de::Error::custom(format_args!(
+                "unknown id of {}: {}",
+                self.expected, id
+            ))),
+        }
+    }
 }
 
-impl<'de, 'a, T: ?Sized + 'static> DeserializeSeed<'de> for MapLookupVisitor<'a, T> {
+impl<'de, 'a, T: ?Sized + Family + 'static> DeserializeSeed<'de> for MapLookupVisitor<'a, T> {
     type Value = DeserializeFn<T>;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer.deserialize_str(self)
+        // Impls are registered with ids only if the trait uses them, in which
+        // case formats that are not human readable hold the id.
+        if !self.registry.ids.is_empty() && !deserializer.is_human_readable() {
+            deserializer.deserialize_u64(self)
+        } else {
+            deserializer.deserialize_str(self)
+        }
     }
 }
 
-pub struct FnApply<T: ?Sized> {
-    pub deserialize_fn: DeserializeFn<T>,
+pub struct FnApply<'c, T: ?Sized + Family> {
+    deserialize_fn: DeserializeFn<T>,
+    context: &'c Context<'c, T>,
+}
+
+impl<'c, T: ?Sized + Family> FnApply<'c, T> {
+    pub fn new(deserialize_fn: DeserializeFn<T>, context: &'c Context<'c, T>) -> Self {
+        FnApply {
+            deserialize_fn,
+            context,
+        }
+    }
 }
 
-impl<'de, T: ?Sized> DeserializeSeed<'de> for FnApply<T> {
-    type Value = Box<T>;
+impl<'de, 'c, T: ?Sized + Family> DeserializeSeed<'de> for FnApply<'c, T> {
+    type Value = Box<Object<'de, T>>;
 
     fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
     where
         D: Deserializer<'de>,
     {
         let mut erased = <dyn erased_serde::Deserializer>::erase(deserializer);
-        (self.deserialize_fn)(&mut erased).map_err(de::Error::custom)
+        (self.deserialize_fn.boxed)(self.context, &mut erased).map_err(de::Error::custom)
     }
 }
@@ -1,6 +1,6 @@
 use crate::de::{FnApply, MapLookupVisitor};
-use crate::private::Registry;
-use crate::ser::Wrap;
+use crate::private::{Context, Family, Object, Registry};
+use crate::ser::{Variant, Wrap};
 use alloc::boxed::Box;
 use core::fmt;
 use serde::de::{self, Deserializer, MapAccess, Visitor};
@@ -9,40 +9,45 @@ use serde::ser::{SerializeMap, Serializer};
 pub fn serialize<S, T>(
     serializer: S,
     variant: &'static str,
+    id: Option<u64>,
     concrete: &T,
 ) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
     T: ?Sized + erased_serde::Serialize,
 {
+    let variant = Variant::new(variant, id, serializer.is_human_readable());
     let mut ser = serializer.serialize_map(Some(1))?;
-    ser.serialize_entry(variant, &Wrap(concrete))?;
+    ser.serialize_entry(&variant, &Wrap(concrete))?;
     ser.end()
 }
 
-pub fn deserialize<'de, D, T>(
+pub fn deserialize<'de, 'c, D, T>(
     deserializer: D,
     trait_object: &'static str,
     registry: &'static Registry<T>,
-) -> Result<Box<T>, D::Error>
+    context: &'c Context<'c, T>,
+) -> Result<Box<Object<'de, T>>, D::Error>
 where
     D: Deserializer<'de>,
-    T: ?Sized,
+    T: ?Sized + Family,
 {
     let visitor = TaggedVisitor {
         trait_object,
         registry,
+        context,
     };
     deserializer.deserialize_map(visitor)
 }
 
-struct TaggedVisitor<T: ?Sized + 'static> {
+struct TaggedVisitor<'c, T: ?Sized + Family + 'static> {
     trait_object: &'static str,
     registry: &'static Registry<T>,
+    context: &'c Context<'c, T>,
 }
 
-impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
-    type Value = Box<T>;
+impl<'de, 'c, T: ?Sized + Family> Visitor<'de> for TaggedVisitor<'c, T> {
+    type Value = Box<Object<'de, T>>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "dyn {}", self.trait_object)
@@ -65,6 +70,6 @@ impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
                 )));
             }
         };
-        map.next_value_seed(FnApply { deserialize_fn })
+        map.next_value_seed(FnApply::new(deserialize_fn, self.context))
     }
 }
@@ -1,7 +1,7 @@
 use crate::content::Content;
 use crate::de::{FnApply, MapLookupVisitor};
-use crate::private::Registry;
-use crate::ser::{InternallyTaggedSerializer, Wrap};
+use crate::private::{Context, Family, Object, Registry};
+use crate::ser::{InternallyTaggedSerializer, Variant, Wrap};
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
 use alloc::string::String;
@@ -18,51 +18,59 @@ pub fn serialize<S, T>(
     serializer: S,
     tag: &'static str,
     variant: &'static str,
+    id: Option<u64>,
+    value_key: &'static str,
     concrete: &T,
 ) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
     T: ?Sized + erased_serde::Serialize,
 {
+    let variant = Variant::new(variant, id, serializer.is_human_readable());
     let adapter = InternallyTaggedSerializer {
         tag,
         variant,
+        value_key,
         delegate: serializer,
     };
     Wrap(concrete).serialize(adapter)
 }
 
-pub fn deserialize<'de, D, T>(
+pub fn deserialize<'de, 'c, D, T>(
     deserializer: D,
     trait_object: &'static str,
     tag: &'static str,
+    value_key: &'static str,
     default_variant: Option<&'static str>,
     registry: &'static Registry<T>,
-) -> Result<Box<T>, D::Error>
+    context: &'c Context<'c, T>,
+) -> Result<Box<Object<'de, T>>, D::Error>
 where
     D: Deserializer<'de>,
-    T: ?Sized,
+    T: ?Sized + Family,
 {
     let visitor = TaggedVisitor {
         trait_object,
         tag,
+        value_key,
         default_variant,
         registry,
+        context,
     };
     deserializer.deserialize_map(visitor)
 }
 
-pub(crate) const DEFAULT_KEY: &str = "value";
-
-struct TaggedVisitor<T: ?Sized + 'static> {
+struct TaggedVisitor<'c, T: ?Sized + Family + 'static> {
     trait_object: &'static str,
     tag: &'static str,
+    value_key: &'static str,
     default_variant: Option<&'static str>,
     registry: &'static Registry<T>,
+    context: &'c Context<'c, T>,
 }
 
-impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
-    type Value = Box<T>;
+impl<'de, 'c, T: ?Sized + Family> Visitor<'de> for TaggedVisitor<'c, T> {
+    type Value = Box<Object<'de, T>>;
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "dyn {}", self.trait_object)
@@ -90,10 +98,11 @@ impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
                 Key::Tag => {
                     let value = map.next_value_seed(map_lookup)?;
                     if entries.is_empty() {
-                        let fn_apply = FnApply {
-                            deserialize_fn: value,
+                        let fn_apply = FnApply::new(value, self.context);
+                        let rest = MapWithStringKeys {
+                            map,
+                            value_key: self.value_key,
                         };
-                        let rest = MapWithStringKeys { map };
                         return fn_apply.deserialize(rest);
                     }
                     deserialize_fn = Some(value);
@@ -120,7 +129,7 @@ impl<'de, T: ?Sized> Visitor<'de> for TaggedVisitor<T> {
             },
         };
 
-        let fn_apply = FnApply { deserialize_fn };
+        let fn_apply = FnApply::new(deserialize_fn, self.context);
         let content = Content::Map(entries).into_deserializer();
         fn_apply.deserialize(content)
     }
@@ -169,6 +178,8 @@ impl<'de> DeserializeSeed<'de> for KeyVisitor {
 
 struct MapWithStringKeys<A> {
     map: A,
+    // Key under which non-map impls hold their value.
+    value_key: &'static str,
 }
 
 impl<'de, A> MapWithStringKeys<A>
@@ -177,8 +188,8 @@ where
 {
     fn try_default_key(&mut self) -> Result<(), A::Error> {
         self.map
-            .next_key_seed(DefaultKey)?
-            .ok_or_else(|| de::Error::missing_field(DEFAULT_KEY))
+            .next_key_seed(DefaultKey(self.value_key))?
+            .ok_or_else(|| de::Error::missing_field(self.value_key))
     }
 }
 
@@ -354,7 +365,7 @@ where
     where
         V: Visitor<'de>,
     {
-        match self.map.next_key_seed(DefaultKey)? {
+        match self.map.next_key_seed(DefaultKey(self.value_key))? {
             None => visitor.visit_none(),
             Some(()) => visitor.visit_some(MapValueAsDeserializer { map: self.map }),
         }
@@ -466,7 +477,11 @@ where
     where
         V: Visitor<'de>,
     {
-        if self.map.next_key_seed(DefaultKey)?.is_some() {
+        if self
+            .map
+            .next_key_seed(DefaultKey(self.value_key))?
+            .is_some()
+        {
             self.map.next_value::<IgnoredAny>()?;
         }
         visitor.visit_unit()
@@ -643,23 +658,29 @@ where
     }
 }
 
-struct DefaultKey;
+#[derive(Copy, Clone)]
+struct DefaultKey(&'static str);
 
 impl<'de> Visitor<'de> for DefaultKey {
     type Value = ();
 
     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        write!(formatter, "the string \"{}\"", DEFAULT_KEY)
+        write!(formatter, "the string \"{}\"", self.0)
     }
 
     fn visit_str<E>(self, string: &str) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
-        if string == DEFAULT_KEY {
+        if string == self.0 {
             Ok(())
         } else {
-            Err(de::Error::unknown_field(string, &[DEFAULT_KEY]))
+            // Same message as de::Error::unknown_field, which needs a static
+            // list of expected fields.
+            Err(de::Error::custom(format_args!(
+                "unknown field `{}`, expected `{}`",
+                string, self.0,
+            )))
         }
     }
 }
@@ -202,6 +202,16 @@
 //! {"Click":{"x":10,"y":10}}
 //! ```
 //!
+//! Lastly `#[typetag::serde(untagged)]` serializes just the value, like Serde's
+//! untagged enums. Deserialization then tries each impl in order of its name
+//! and keeps the first one that succeeds.
+//!
+//! For formats with their own enum syntax, like RON's `Click(...)` or YAML's
+//! `!Click` tags, `#[typetag::serde(variant)]` serializes each impl as a
+//! newtype variant of an enum named after the trait. The variant index that
+//! compact formats write is the id of the impl, described under the question
+//! about compact formats below, so it stays stable as impls are added.
+//!
 //! Separately, the value of the tag for a given trait impl may be defined as
 //! part of the attribute that goes on the trait impl. By default the tag will
 //! be the type name when no name is specified explicitly.
@@ -232,6 +242,12 @@
 //! {"type":"mouse_button_down","x":10,"y":10}
 //! ```
 //!
+//! To derive the default names of all impls with one of Serde's case
+//! conventions instead, put `rename_all = "snake_case"` in the attribute on the
+//! trait. Adding `name_strategy = "path"` prefixes default names with the
+//! module path of the impl, as in `my_crate::events::Click`, so that same-named
+//! types from different crates do not collide.
+//!
 //! Conceptually all you're getting with this crate is that we build for you an
 //! enum in which every impl of the trait in your program is automatically
 //! registered as an enum variant. The behavior is the same as if you had
@@ -271,11 +287,120 @@
 //!   All three choices of enum representation will round-trip correctly through
 //!   compact binary formats including Bincode.
 //!
+//! - *Can the tags be smaller than a string in compact formats?* **Yes**
+//!
+//!   With `tag_kind = "id"` on the trait, formats that are not human readable
+//!   write a numeric id in place of the name, while formats like JSON keep
+//!   using names. The id is a 32-bit hash of the name computed at compile time,
+//!   unless the impl gives one as in `#[typetag::serde(id = 17)]`. Colliding
+//!   ids are reported the same way as colliding names.
+//!
+//! - *Can trait objects deserialize into `Rc` or `Arc`?* **Yes**
+//!
+//!   Annotate the field with
+//!   `#[serde(deserialize_with = "typetag::deserialize_arc")]`, or
+//!   `deserialize_rc`. These deserialize a `Box<dyn Trait>` and move it into
+//!   the pointer, like serde's `rc` feature does for sized types.
+//!
+//! - *Can `Box<dyn Trait>` implement `Clone`?* **Yes**
+//!
+//!   Implement it with `typetag::clone_box(&**self)`, which serializes the value
+//!   into an in-memory buffer and deserializes a copy from it through the
+//!   registry, without involving any data format.
+//!
+//! - *Can trait objects be compared, hashed, or printed?* **Yes**
+//!
+//!   Declare the trait with
+//!   `#[typetag::serde(derive(Debug, PartialEq, Eq, Hash))]`, or any of those,
+//!   without bounding the impls by them. Trait objects are then compared and
+//!   hashed by their tag and serialized content, with map entries in a fixed
+//!   order and `-0.0` and NaN normalized, and print like `Click { x: 10, y: 10 }`.
+//!   Comparing or hashing a value whose serialization fails panics, and
+//!   printing it fails.
+//!
+//! - *Can trait objects be described by a JSON Schema?* **Yes**
+//!
+//!   Enable the `schemars` feature and declare the trait with
+//!   `#[typetag::serde(schema)]`. Its impls then need to implement
+//!   `schemars::JsonSchema`, and `dyn Trait` gets a schema that is a `oneOf`
+//!   over every registered impl, in the representation of the trait: a map
+//!   keyed by the tag, or the tag as a property alongside or next to the
+//!   content.
+//!
+//! - *Can impls deserialize with some context, like an interner?* **Yes**
+//!
+//!   Declare the trait with `#[typetag::serde(seed = Context)]` and implement
+//!   [`typetag::DeserializeWith`] for each impl in place of `Deserialize`. The
+//!   trait objects then deserialize through the `DeserializeSeed`
+//!   [`typetag::Seed::new(&context)`][`typetag::Seed`]. The context may borrow,
+//!   as in `seed = Interner<'_>`, in which case its lifetime is that of the
+//!   reference given to `Seed::new`.
+//!
+//! - *Can impls borrow from the input instead of copying it?* **Yes**
+//!
+//!   Give the trait one lifetime parameter for the input and declare it with
+//!   `#[typetag::serde(borrow)]`, as in `trait Event<'de>`. Impls like
+//!   `impl<'de> Event<'de> for Line<'de>` may then hold `&'de str` fields, and
+//!   `Box<dyn Event<'de> + 'de>` deserializes from input that lives for `'de`.
+//!   This does not combine with the `runtime-registration` feature.
+//!
 //! - *Does it support non-struct types?* **Yes**
 //!
 //!   The implementations of the trait can be structs, enums, primitives, or
 //!   anything else supported by Serde. The Serialize and Deserialize impls may
-//!   be derived or handwritten.
+//!   be derived or handwritten. With an internal tag, values that are not maps
+//!   go under a `"value"` key next to the tag, which `value_key = "data"` on the
+//!   trait renames.
+//!
+//! - *Does it support generic traits?* **Yes**
+//!
+//!   Each deserializable trait object needs its own registry, so list the ones
+//!   you want as `#[typetag::serde(instantiate(Residual<f64>, Residual<f32>))]`.
+//!   Impls register into whichever instantiation they implement. Likewise a
+//!   generic impl lists the types to register along with their tags, as in
+//!   `#[typetag::serde(instantiate(Between<SE3> = "BetweenSE3"))]`, which need
+//!   to be `'static`. Other instantiations keep the impl, but have no tag, so
+//!   serializing them returns an error.
+//!
+//! - *Does it support traits with associated types?* **Yes**
+//!
+//!   The same way as generic traits: list the bindings to deserialize, as in
+//!   `#[typetag::serde(instantiate(Model<Output = f64>))]`, and each impl
+//!   registers into the trait object matching its associated types.
+//!
+//! - *Can impls of a subtrait deserialize as the supertrait?* **Yes**
+//!
+//!   Every impl of `trait Derived: Base` also implements `Base`, and putting
+//!   `#[typetag::serde]` on that impl registers it for `Box<dyn Base>` under
+//!   the tag that it serializes with as `dyn Base`.
+//!
+//! - *Can I rename an impl without breaking previously serialized data?* **Yes**
+//!
+//!   Keep the old tags as aliases, as in
+//!   `#[typetag::serde(name = "Between", alias = "BetweenFactor")]`. Aliases are
+//!   accepted when deserializing while serialization always uses the name.
+//!
+//! - *Can my crate replace the impl that a dependency registered for a tag?*
+//!   **Yes**
+//!
+//!   Register yours with `#[typetag::serde(name = "Huber", override)]` and it wins
+//!   over the other impls of that tag. Two overrides of the same tag are still an
+//!   error.
+//!
+//! - *Can a program pass through data of impls it does not know?* **Yes**
+//!
+//!   Put `unknown = "Unknown"` on the trait and register an impl for
+//!   [`typetag::Unknown`] under that name. Unknown tags deserialize into it,
+//!   and it serializes back to the original payload. So does input tagged with
+//!   that name itself.
+//!
+//! - *Does it work on targets without static constructors, like WebAssembly?*
+//!   **Yes**
+//!
+//!   Enable the `runtime-registration` feature and call
+//!   `typetag::register::<dyn WebEvent, Click>("Click")` for each impl at startup,
+//!   before the first deserialization, in place of the automatic registration.
+//!   With `default-features = false` the `inventory` dependency is left out.
 //!
 //! - *Didn't someone explain to me why this wasn't possible?* **Yes**
 //!
@@ -295,6 +420,9 @@
 //! [`inventory`]: https://github.com/dtolnay/inventory
 //! [`ctor`]: https://github.com/mmastrac/rust-ctor
 //! [`erased-serde`]: https://github.com/dtolnay/erased-serde
+//! [`typetag::Unknown`]: crate::Unknown
+//! [`typetag::DeserializeWith`]: crate::DeserializeWith
+//! [`typetag::Seed`]: crate::Seed
 
 #![no_std]
 #![doc(html_root_url = "https://docs.rs/typetag/0.2.19")]
@@ -307,17 +435,45 @@
 )]
 
 extern crate alloc;
+#[cfg(feature = "runtime-registration")]
+extern crate std;
+
+#[cfg(not(any(feature = "inventory", feature = "runtime-registration")))]
+compile_error!("typetag requires either the default `inventory` feature or `runtime-registration`");
 
 mod adjacently;
+mod clone;
 mod content;
 mod de;
 mod externally;
 mod internally;
 mod is_serialize_str;
+mod rc;
+mod registry;
+#[cfg(feature = "runtime-registration")]
+mod runtime;
+#[cfg(feature = "schemars")]
+mod schema;
+mod seed;
 mod ser;
+mod structural;
+mod unknown;
+mod untagged;
+mod validate;
+mod variant;
 
 use self::__private as private;
 
+pub use crate::clone::clone_box;
+#[cfg(target_has_atomic = "ptr")]
+pub use crate::rc::deserialize_arc;
+pub use crate::rc::deserialize_rc;
+pub use crate::registry::registered_names;
+#[cfg(feature = "runtime-registration")]
+pub use crate::runtime::{register, register_with_aliases};
+pub use crate::seed::{DeserializeWith, Seed};
+pub use crate::unknown::Unknown;
+pub use crate::validate::{validate, Duplicate, DuplicateId, ImplInfo, ValidationError};
 pub use typetag_impl::{deserialize, serde, serialize};
 
 // Object-safe trait bound inserted by typetag serialization. We want this just
@@ -341,10 +497,14 @@ impl<T> Deserialize for T {}
 pub mod __private {
     #[doc(hidden)]
     pub extern crate erased_serde;
+    #[cfg(feature = "inventory")]
     #[doc(hidden)]
     pub extern crate inventory;
     #[doc(hidden)]
     pub extern crate once_cell;
+    #[cfg(feature = "schemars")]
+    #[doc(hidden)]
+    pub extern crate schemars;
     #[doc(hidden)]
     pub extern crate serde;
 
@@ -354,6 +514,24 @@ pub mod __private {
         pub use crate::externally::*;
     }
 
+    #[doc(hidden)]
+    pub mod untagged {
+        #[doc(hidden)]
+        pub use crate::untagged::*;
+    }
+
+    #[doc(hidden)]
+    pub mod unknown {
+        #[doc(hidden)]
+        pub use crate::unknown::{deserialize, Tagging};
+    }
+
+    #[doc(hidden)]
+    pub mod variant {
+        #[doc(hidden)]
+        pub use crate::variant::*;
+    }
+
     #[doc(hidden)]
     pub mod internally {
         #[doc(hidden)]
@@ -366,36 +544,429 @@ pub mod __private {
         pub use crate::adjacently::*;
     }
 
+    #[cfg(feature = "schemars")]
+    #[doc(hidden)]
+    pub mod schema {
+        #[doc(hidden)]
+        pub use crate::schema::*;
+    }
+
+    #[doc(hidden)]
+    pub mod structural {
+        #[doc(hidden)]
+        pub use crate::structural::*;
+    }
+
+    #[doc(hidden)]
+    pub use core::any::{type_name, Any, TypeId};
+    #[doc(hidden)]
+    pub use core::cmp::{Eq, PartialEq};
+    #[doc(hidden)]
+    pub use core::concat;
     #[doc(hidden)]
-    pub use alloc::collections::btree_map;
+    pub use core::fmt;
+    #[doc(hidden)]
+    pub use core::hash::{Hash, Hasher};
+    #[doc(hidden)]
+    pub use core::marker::{Send, Sync, Unpin};
+    #[doc(hidden)]
+    pub use core::module_path;
     #[doc(hidden)]
     pub use core::option::Option;
     #[doc(hidden)]
+    pub use core::panic::{RefUnwindSafe, UnwindSafe};
+    #[doc(hidden)]
     pub use core::result::Result;
+    #[cfg(feature = "schemars")]
+    #[doc(hidden)]
+    pub type Cow<'a, T> = alloc::borrow::Cow<'a, T>;
 
     #[doc(hidden)]
     pub type Box<T> = alloc::boxed::Box<T>;
     #[doc(hidden)]
     pub type BTreeMap<K, V> = alloc::collections::BTreeMap<K, V>;
     #[doc(hidden)]
-    pub type BTreeMapEntry<'a, K, V> = alloc::collections::btree_map::Entry<'a, K, V>;
-    #[doc(hidden)]
     pub type Vec<T> = alloc::vec::Vec<T>;
 
+    // The trait object that impls registered under T deserialize into, from
+    // input that lives for 'de. That is T itself except for traits declared
+    // with `borrow`, whose registry is keyed by the 'static trait object.
+    #[doc(hidden)]
+    pub trait Lifetime<'de> {
+        type Object: ?Sized;
+    }
+
+    // The context that impls registered under T deserialize with, borrowed
+    // for 'ctx: that of a typetag::Seed, or `()` for traits that do not take
+    // one. The context may itself borrow for 'ctx.
+    #[doc(hidden)]
+    pub trait Contextual<'ctx> {
+        type Context: 'ctx;
+    }
+
+    #[doc(hidden)]
+    pub trait Family: for<'de> Lifetime<'de> + for<'ctx> Contextual<'ctx> {}
+
+    impl<T> Family for T where T: ?Sized + for<'de> Lifetime<'de> + for<'ctx> Contextual<'ctx> {}
+
+    #[doc(hidden)]
+    pub type Object<'de, T> = <T as Lifetime<'de>>::Object;
+
+    #[doc(hidden)]
+    pub type Context<'ctx, T> = <T as Contextual<'ctx>>::Context;
+
+    // Deserialization of one impl into a Box. Impls of a trait with a seed find its context in the first argument. Impls of a
+    // trait declared with `schema` also describe their input.
     #[doc(hidden)]
-    pub type DeserializeFn<T> =
-        fn(&mut dyn erased_serde::Deserializer) -> erased_serde::Result<Box<T>>;
+    pub struct DeserializeFn<T: ?Sized + Family> {
+        pub boxed: BoxFn<T>,
+        #[cfg(feature = "schemars")]
+        pub schema: Option<SchemaFn>,
+    }
+
+    #[cfg(feature = "schemars")]
+    #[doc(hidden)]
+    pub type SchemaFn = fn(&mut schemars::SchemaGenerator) -> schemars::Schema;
+
+    type BoxFn<T> = for<'de, 'ctx> fn(
+        &'ctx Context<'ctx, T>,
+        &mut dyn erased_serde::Deserializer<'de>,
+    ) -> erased_serde::Result<Box<Object<'de, T>>>;
+
+    impl<T: ?Sized + Family> Copy for DeserializeFn<T> {}
+
+    impl<T: ?Sized + Family> Clone for DeserializeFn<T> {
+        fn clone(&self) -> Self {
+            *self
+        }
+    }
 
     #[doc(hidden)]
-    pub struct Registry<T: ?Sized> {
+    pub struct Registry<T: ?Sized + Family> {
         #[doc(hidden)]
         pub map: BTreeMap<&'static str, Option<DeserializeFn<T>>>,
         #[doc(hidden)]
         pub names: Vec<&'static str>,
+        #[doc(hidden)]
+        pub ids: BTreeMap<u64, Option<DeserializeFn<T>>>,
+        #[doc(hidden)]
+        pub duplicates: Vec<crate::Duplicate>,
+        #[doc(hidden)]
+        pub duplicate_ids: Vec<crate::DuplicateId>,
+    }
+
+    // How impls without a name derive their tag, from the rename_all and
+    // name_strategy arguments on the trait. Impls give their type name along
+    // with the byte offset of each word after the first, and the tag is built
+    // at compile time into an array of the length it computes.
+    #[doc(hidden)]
+    #[derive(Copy, Clone)]
+    pub struct DefaultName {
+        pub rename_all: RenameRule,
+        pub path: bool,
+    }
+
+    impl DefaultName {
+        #[doc(hidden)]
+        pub const fn len(self, module_path: &str, name: &str, words: &[usize]) -> usize {
+            let mut len = 0;
+            let mut cursor = NameCursor::START;
+            while let Some((_byte, next)) = self.next(module_path, name, words, cursor) {
+                len += 1;
+                cursor = next;
+            }
+            len
+        }
+
+        #[doc(hidden)]
+        pub const fn build<const N: usize>(
+            self,
+            module_path: &str,
+            name: &str,
+            words: &[usize],
+        ) -> [u8; N] {
+            let mut tag = [0; N];
+            let mut i = 0;
+            let mut cursor = NameCursor::START;
+            while let Some((byte, next)) = self.next(module_path, name, words, cursor) {
+                tag[i] = byte;
+                i += 1;
+                cursor = next;
+            }
+            tag
+        }
+
+        // Generic impls, whose trait object cannot be named where the tag is
+        // built, list their type name under every rename rule, in declaration
+        // order, both bare and prefixed by their module path, for the trait to
+        // pick from at compile time.
+        #[doc(hidden)]
+        pub const fn select(
+            self,
+            names: [&'static str; 9],
+            paths: [&'static str; 9],
+        ) -> &'static str {
+            let i = self.rename_all as usize;
+            if self.path {
+                paths[i]
+            } else {
+                names[i]
+            }
+        }
+
+        #[doc(hidden)]
+        pub const fn as_str(tag: &'static [u8]) -> &'static str {
+            // `build` only copies bytes of the module path and type name,
+            // changes the case of ASCII letters, and inserts ASCII separators
+            // at char boundaries, so the tag remains UTF-8.
+            match core::str::from_utf8(tag) {
+                Ok(tag) => tag,
+                Err(_) => panic!("typetag: default name is not UTF-8"),
+            }
+        }
+
+        // The byte of the tag at the cursor, and the cursor after it.
+        const fn next(
+            self,
+            module_path: &str,
+            name: &str,
+            words: &[usize],
+            cursor: NameCursor,
+        ) -> Option<(u8, NameCursor)> {
+            let NameCursor { part, i } = cursor;
+            match part {
+                NamePart::Path if self.path && i < module_path.len() + 2 => {
+                    let byte = if i < module_path.len() {
+                        module_path.as_bytes()[i]
+                    } else {
+                        b':'
+                    };
+                    Some((byte, NameCursor { part, i: i + 1 }))
+                }
+                NamePart::Path => self.next(module_path, name, words, NameCursor::NAME),
+                NamePart::Name if i == name.len() => None,
+                NamePart::Name if i > 0 && is_word(words, name, i) => {
+                    let separator = match self.rename_all {
+                        RenameRule::SnakeCase | RenameRule::ScreamingSnakeCase => b'_',
+                        RenameRule::KebabCase | RenameRule::ScreamingKebabCase => b'-',
+                        _ => return self.next(module_path, name, words, cursor.separated()),
+                    };
+                    Some((separator, cursor.separated()))
+                }
+                NamePart::Name | NamePart::Separated => {
+                    let byte = name.as_bytes()[i];
+                    let byte = match self.rename_all {
+                        RenameRule::None | RenameRule::PascalCase => byte,
+                        RenameRule::CamelCase if i == 0 => byte.to_ascii_lowercase(),
+                        RenameRule::CamelCase => byte,
+                        RenameRule::LowerCase | RenameRule::SnakeCase | RenameRule::KebabCase => {
+                            byte.to_ascii_lowercase()
+                        }
+                        RenameRule::UpperCase
+                        | RenameRule::ScreamingSnakeCase
+                        | RenameRule::ScreamingKebabCase => byte.to_ascii_uppercase(),
+                    };
+                    let next = NameCursor {
+                        part: NamePart::Name,
+                        i: i + 1,
+                    };
+                    Some((byte, next))
+                }
+            }
+        }
+    }
+
+    // Whether a word of the type name starts at byte `i`, which must be a char
+    // boundary for separators to go in front of it.
+    const fn is_word(words: &[usize], name: &str, i: usize) -> bool {
+        let mut w = 0;
+        while w < words.len() {
+            if words[w] == i {
+                assert!(
+                    name.as_bytes()[i] & 0xC0 != 0x80,
+                    "word does not start at a char boundary",
+                );
+                return true;
+            }
+            w += 1;
+        }
+        false
+    }
+
+    #[derive(Copy, Clone)]
+    struct NameCursor {
+        part: NamePart,
+        i: usize,
+    }
+
+    impl NameCursor {
+        const START: Self = NameCursor {
+            part: NamePart::Path,
+            i: 0,
+        };
+
+        const NAME: Self = NameCursor {
+            part: NamePart::Name,
+            i: 0,
+        };
+
+        const fn separated(self) -> Self {
+            NameCursor {
+                part: NamePart::Separated,
+                i: self.i,
+            }
+        }
+    }
+
+    // Whether the cursor is in the module path and the `::` after it, before
+    // a byte of the type name, or past the separator in front of that byte.
+    #[derive(Copy, Clone)]
+    enum NamePart {
+        Path,
+        Name,
+        Separated,
+    }
+
+    #[doc(hidden)]
+    #[derive(Copy, Clone)]
+    pub enum RenameRule {
+        None,
+        LowerCase,
+        UpperCase,
+        PascalCase,
+        CamelCase,
+        SnakeCase,
+        ScreamingSnakeCase,
+        KebabCase,
+        ScreamingKebabCase,
+    }
+
+    // Id of an impl that does not give one explicitly, as the 32-bit FNV-1a
+    // hash of its name. Evaluated at compile time wherever the name is const.
+    #[doc(hidden)]
+    pub const fn hash_id(name: &str) -> u64 {
+        let bytes = name.as_bytes();
+        let mut hash: u32 = 0x811c_9dc5;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u32;
+            hash = hash.wrapping_mul(0x0100_0193);
+            i += 1;
+        }
+        hash as u64
+    }
+
+    // Explicit ids are only accepted on impls of traits tagged by id, which
+    // is checked at compile time against the trait object of the impl.
+    #[doc(hidden)]
+    pub const fn require_tag_kind_id(tagged_by_id: bool) {
+        if !tagged_by_id {
+            panic!("#[typetag::serde(id = ...)] requires the trait to be declared with tag_kind = \"id\"");
+        }
+    }
+
+    // One impl registered into a Registry, under its name and aliases.
+    #[doc(hidden)]
+    pub struct Registration<T: ?Sized + Family> {
+        pub name: &'static str,
+        pub aliases: &'static [&'static str],
+        pub deserializer: DeserializeFn<T>,
+        pub type_name: fn() -> &'static str,
+        pub module_path: &'static str,
+        pub is_override: bool,
+        pub id: Option<u64>,
+    }
+
+    impl<T: ?Sized + Family> Copy for Registration<T> {}
+
+    impl<T: ?Sized + Family> Clone for Registration<T> {
+        fn clone(&self) -> Self {
+            *self
+        }
     }
 
     #[doc(hidden)]
     pub trait Strictest {
-        type Object: ?Sized;
+        type Object: ?Sized + Family + 'static;
+    }
+
+    // Implemented by every trait object that deserialization is generated
+    // for, with the registry of impls that deserialization dispatches to.
+    #[doc(hidden)]
+    pub trait Registered: Strictest {
+        fn registry() -> &'static Registry<Self::Object>;
+
+        #[cfg(feature = "runtime-registration")]
+        fn runtime() -> &'static RuntimeRegistry<Self::Object>;
+    }
+
+    #[cfg(feature = "runtime-registration")]
+    #[doc(hidden)]
+    pub use crate::runtime::RuntimeRegistry;
+
+    // Implemented by the trait object for every impl of the trait, to produce
+    // the deserialize function that the impl registers.
+    #[doc(hidden)]
+    pub trait Construct<T>: Strictest {
+        fn deserialize_fn() -> DeserializeFn<Self::Object>;
+
+        #[cfg(feature = "runtime-registration")]
+        fn id() -> Option<u64>;
+    }
+
+    // Implemented by trait objects of traits declared with a seed, in place of
+    // Deserialize, for typetag::Seed to deserialize with the context.
+    #[doc(hidden)]
+    pub trait Seeded: for<'ctx> Contextual<'ctx> {
+        fn deserialize_seeded<'de, 'ctx, D>(
+            context: &'ctx Context<'ctx, Self>,
+            deserializer: D,
+        ) -> Result<Box<Self>, D::Error>
+        where
+            D: serde::Deserializer<'de>;
+    }
+
+    // Tag of one instantiation of a generic impl, registered into the trait
+    // object T.
+    #[doc(hidden)]
+    pub trait InstanceName<T: ?Sized> {
+        const NAME: &'static str;
+    }
+
+    // Tag of the instantiation of a generic impl whose type is `this`, among
+    // the listed instantiations and their tags. An instantiation that is not
+    // listed has no tag.
+    #[doc(hidden)]
+    pub fn instance_name(this: TypeId, instances: &[Instance]) -> Option<&'static str> {
+        for (type_id, name) in instances {
+            if *type_id == this {
+                return Some(name);
+            }
+        }
+        None
+    }
+
+    // Type of an instantiation along with its tag.
+    #[doc(hidden)]
+    pub type Instance = (TypeId, &'static str);
+
+    // Error serializing an instantiation of a generic impl that has no tag.
+    #[doc(hidden)]
+    pub fn unlisted<E>(trait_object: &str) -> E
+    where
+        E: serde::ser::Error,
+    {
+        E::custom(format_args!(
+            "unlisted instantiation of a generic impl of dyn {}",
+            trait_object
+        ))
+    }
+
+    // Numeric id of an impl, for registering it into the trait object T at
+    // runtime.
+    #[cfg(feature = "runtime-registration")]
+    #[doc(hidden)]
+    pub trait InstanceId<T: ?Sized> {
+        const ID: u64;
     }
 }
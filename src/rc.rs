@@ -0,0 +1,90 @@
+use alloc::boxed::Box;
+use alloc::rc::Rc;
+#[cfg(target_has_atomic = "ptr")]
+use alloc::sync::Arc;
+use serde::{Deserialize, Deserializer};
+
+/// Deserializes a trait object into an `Rc`.
+///
+/// The value is deserialized into a `Box` and moved into the `Rc`.
+///
+/// Serde implements `Deserialize` for `Rc<T>` only for sized `T`, so use this
+/// through `deserialize_with` instead. It works for any `T` whose `Box<T>`
+/// deserializes, trait objects included.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use std::rc::Rc;
+///
+/// #[typetag::serde]
+/// trait Shape {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Circle {
+///     radius: f64,
+/// }
+///
+/// #[typetag::serde]
+/// impl Shape for Circle {}
+///
+/// #[derive(Deserialize)]
+/// struct Scene {
+///     #[serde(deserialize_with = "typetag::deserialize_rc")]
+///     shape: Rc<dyn Shape>,
+/// }
+/// #
+/// # #[cfg(feature = "runtime-registration")]
+/// # typetag::register::<dyn Shape, Circle>("Circle");
+///
+/// let json = r#"{"shape":{"Circle":{"radius":1.0}}}"#;
+/// let scene: Scene = serde_json::from_str(json).unwrap();
+/// # let _ = scene.shape;
+/// ```
+pub fn deserialize_rc<'de, D, T>(deserializer: D) -> Result<Rc<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ?Sized,
+    Box<T>: Deserialize<'de>,
+{
+    Box::<T>::deserialize(deserializer).map(Rc::from)
+}
+
+/// Deserializes a trait object into an `Arc`.
+///
+/// Like [`deserialize_rc`], for use through `deserialize_with`.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use std::sync::Arc;
+///
+/// #[typetag::serde]
+/// trait Factor: Send + Sync {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Prior;
+///
+/// #[typetag::serde]
+/// impl Factor for Prior {}
+///
+/// #[derive(Deserialize)]
+/// struct Graph {
+///     #[serde(deserialize_with = "typetag::deserialize_arc")]
+///     factor: Arc<dyn Factor + Send + Sync>,
+/// }
+/// #
+/// # #[cfg(feature = "runtime-registration")]
+/// # typetag::register::<dyn Factor, Prior>("Prior");
+///
+/// let json = r#"{"factor":{"Prior":null}}"#;
+/// let graph: Graph = serde_json::from_str(json).unwrap();
+/// # let _ = graph.factor;
+/// ```
+#[cfg(target_has_atomic = "ptr")]
+pub fn deserialize_arc<'de, D, T>(deserializer: D) -> Result<Arc<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ?Sized,
+    Box<T>: Deserialize<'de>,
+{
+    Box::<T>::deserialize(deserializer).map(Arc::from)
+}
@@ -0,0 +1,143 @@
+use crate::private::{DeserializeFn, Family, Registered, Registration, Registry};
+use crate::validate::{Duplicate, DuplicateId, ImplInfo};
+use alloc::collections::btree_map::BTreeMap;
+use alloc::vec::Vec;
+
+impl<T: ?Sized + Family> Registry<T> {
+    pub fn new<I>(registrations: I) -> Self
+    where
+        I: IntoIterator<Item = Registration<T>>,
+    {
+        let mut by_tag: BTreeMap<&'static str, Vec<Candidate<T>>> = BTreeMap::new();
+        let mut by_id: BTreeMap<u64, Vec<Candidate<T>>> = BTreeMap::new();
+        let mut names = Vec::new();
+        for registration in registrations {
+            let candidate = Candidate {
+                deserializer: registration.deserializer,
+                is_override: registration.is_override,
+                info: ImplInfo {
+                    type_name: (registration.type_name)(),
+                    module_path: registration.module_path,
+                },
+            };
+            // Aliases deserialize like the name but are left out of the
+            // expected variants, the same as serde's #[serde(alias)].
+            for tag in Some(&registration.name)
+                .into_iter()
+                .chain(registration.aliases)
+            {
+                by_tag.entry(*tag).or_default().push(candidate);
+            }
+            if let Some(id) = registration.id {
+                by_id.entry(id).or_default().push(candidate);
+            }
+            names.push(registration.name);
+        }
+        names.sort_unstable();
+        names.dedup();
+
+        let (map, duplicates) = resolve(by_tag);
+        let duplicates = duplicates
+            .into_iter()
+            .map(|(tag, impls)| Duplicate { tag, impls })
+            .collect();
+        let (ids, duplicate_ids) = resolve(by_id);
+        let duplicate_ids = duplicate_ids
+            .into_iter()
+            .map(|(id, impls)| DuplicateId { id, impls })
+            .collect();
+
+        Registry {
+            map,
+            names,
+            ids,
+            duplicates,
+            duplicate_ids,
+        }
+    }
+}
+
+type Resolved<K, T> = (
+    BTreeMap<K, Option<DeserializeFn<T>>>,
+    Vec<(K, Vec<ImplInfo>)>,
+);
+
+// Picks the impl that deserializes each key, leaving None along with a report
+// of the conflicting impls for keys that are ambiguous.
+fn resolve<K, T>(candidates: BTreeMap<K, Vec<Candidate<T>>>) -> Resolved<K, T>
+where
+    K: Ord + Copy,
+    T: ?Sized + Family,
+{
+    let mut map = BTreeMap::new();
+    let mut duplicates = Vec::new();
+    for (key, mut candidates) in candidates {
+        // An impl registered with `override` replaces the others of the same
+        // key, so only overrides colliding with each other remain ambiguous.
+        if candidates.iter().any(|candidate| candidate.is_override) {
+            candidates.retain(|candidate| candidate.is_override);
+        }
+        if let [candidate] = *candidates {
+            map.insert(key, Some(candidate.deserializer));
+        } else {
+            map.insert(key, None);
+            let impls = candidates.iter().map(|candidate| candidate.info).collect();
+            duplicates.push((key, impls));
+        }
+    }
+    (map, duplicates)
+}
+
+struct Candidate<T: ?Sized + Family> {
+    deserializer: DeserializeFn<T>,
+    is_override: bool,
+    info: ImplInfo,
+}
+
+impl<T: ?Sized + Family> Copy for Candidate<T> {}
+
+impl<T: ?Sized + Family> Clone for Candidate<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+/// Tags of every impl registered for deserializing the trait object `T`,
+/// in sorted order.
+///
+/// This is the same list that deserialization errors report as the expected
+/// variants, which makes it handy for listing the available plugins or for
+/// asserting that every expected impl got linked into the program.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[typetag::serde]
+/// trait WebEvent {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct PageLoad;
+///
+/// #[typetag::serde]
+/// impl WebEvent for PageLoad {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Click;
+///
+/// #[typetag::serde]
+/// impl WebEvent for Click {}
+/// #
+/// # #[cfg(feature = "runtime-registration")]
+/// # {
+/// #     typetag::register::<dyn WebEvent, PageLoad>("PageLoad");
+/// #     typetag::register::<dyn WebEvent, Click>("Click");
+/// # }
+///
+/// assert_eq!(typetag::registered_names::<dyn WebEvent>(), ["Click", "PageLoad"]);
+/// ```
+pub fn registered_names<T>() -> &'static [&'static str]
+where
+    T: ?Sized + Registered,
+{
+    &T::registry().names
+}
@@ -0,0 +1,147 @@
+use crate::private::{Construct, Family, Registered, Registration, Registry};
+use alloc::vec::Vec;
+use once_cell::sync::{Lazy, OnceCell};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Registers `T` for deserializing the trait object `O` under the tag `name`.
+///
+/// This is only available with the `runtime-registration` feature, which
+/// replaces the automatic registration performed by `#[typetag::serde]` on
+/// impl blocks. Use it on targets where the static constructors that the
+/// automatic registration relies on never run. The name should match the tag
+/// that the impl serializes with.
+///
+/// # Panics
+///
+/// Every impl needs to be registered before the trait object is first
+/// deserialized, or otherwise looks up its registry, such as through
+/// [`registered_names`][crate::registered_names]. Registering afterward panics.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[typetag::serde]
+/// trait WebEvent {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct PageLoad;
+///
+/// #[typetag::serde]
+/// impl WebEvent for PageLoad {}
+///
+/// typetag::register::<dyn WebEvent, PageLoad>("PageLoad");
+///
+/// let event: Box<dyn WebEvent> = serde_json::from_str(r#"{"PageLoad":null}"#).unwrap();
+/// ```
+pub fn register<O, T>(name: &'static str)
+where
+    O: ?Sized + Registered + Construct<T>,
+{
+    register_with_aliases::<O, T>(name, &[]);
+}
+
+/// Registers `T` like [`register`], additionally accepting each of `aliases`
+/// as its tag when deserializing.
+///
+/// This corresponds to `alias = "..."` in `#[typetag::serde(...)]` on an impl
+/// block, which has no effect with the `runtime-registration` feature. Neither
+/// does `override`, since the program chooses what to register.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[typetag::serde]
+/// trait WebEvent {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Click;
+///
+/// #[typetag::serde(alias = "MouseDown")]
+/// impl WebEvent for Click {}
+///
+/// typetag::register_with_aliases::<dyn WebEvent, Click>("Click", &["MouseDown"]);
+///
+/// let event: Box<dyn WebEvent> = serde_json::from_str(r#"{"MouseDown":null}"#).unwrap();
+/// ```
+pub fn register_with_aliases<O, T>(name: &'static str, aliases: &'static [&'static str])
+where
+    O: ?Sized + Registered + Construct<T>,
+{
+    let type_name = core::any::type_name::<T>();
+    let registered = O::runtime().push(Registration {
+        name,
+        aliases,
+        deserializer: O::deserialize_fn(),
+        type_name: core::any::type_name::<T>,
+        module_path: module_of(type_name),
+        is_override: false,
+        id: O::id(),
+    });
+    if !registered {
+        panic!(
+            "{} registered into {} after its registry was first used",
+            type_name,
+            core::any::type_name::<O>(),
+        );
+    }
+}
+
+// Without the impl block at hand, report the module that defines the type.
+fn module_of(type_name: &'static str) -> &'static str {
+    let path = match type_name.find('<') {
+        Some(generics) => &type_name[..generics],
+        None => type_name,
+    };
+    match path.rfind("::") {
+        Some(last) => &path[..last],
+        None => "",
+    }
+}
+
+// Registrations of one trait object, gathered until its registry is first
+// used and then built into it once.
+pub struct RuntimeRegistry<T: ?Sized + Family + 'static> {
+    registrations: Lazy<Mutex<Vec<Registration<T>>>>,
+    registry: OnceCell<Registry<T>>,
+}
+
+impl<T: ?Sized + Family> RuntimeRegistry<T> {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        RuntimeRegistry {
+            registrations: Lazy::new(Mutex::default),
+            registry: OnceCell::new(),
+        }
+    }
+
+    // Returns false if the registry was already built.
+    fn push(&self, registration: Registration<T>) -> bool {
+        let mut registrations = self.lock();
+        if self.registry.get().is_some() {
+            return false;
+        }
+        registrations.push(registration);
+        true
+    }
+
+    pub fn registry(&'static self) -> &'static Registry<T> {
+        if let Some(registry) = self.registry.get() {
+            return registry;
+        }
+
+        // Holding the lock while building keeps any registration from
+        // slipping in between reading the registrations and publishing the
+        // registry built from them.
+        let registrations = self.lock();
+        self.registry
+            .get_or_init(|| Registry::new(registrations.iter().copied()))
+    }
+
+    // Registrations are only ever pushed whole, so a panic elsewhere while
+    // the lock was held leaves them intact.
+    fn lock(&self) -> MutexGuard<'_, Vec<Registration<T>>> {
+        self.registrations
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,239 @@
+use crate::private::{Family, Registry, SchemaFn};
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use schemars::{json_schema, Schema, SchemaGenerator};
+use serde_json::{json, Value};
+
+// Used from the JsonSchema impls generated for traits declared with `schema`.
+// Each builds a `oneOf` over the registered impls, with the schema of every
+// impl placed where the representation of the trait puts its data.
+
+pub fn externally<T>(generator: &mut SchemaGenerator, registry: &'static Registry<T>) -> Schema
+where
+    T: ?Sized + Family,
+{
+    one_of(impls(registry).map(|(name, schema)| {
+        json!({
+            "type": "object",
+            "properties": {
+                name: schema(generator),
+            },
+            "required": [name],
+            "additionalProperties": false,
+        })
+    }))
+}
+
+pub fn untagged<T>(generator: &mut SchemaGenerator, registry: &'static Registry<T>) -> Schema
+where
+    T: ?Sized + Family,
+{
+    one_of(impls(registry).map(|(_name, schema)| schema(generator).to_value()))
+}
+
+pub fn internally<T>(
+    generator: &mut SchemaGenerator,
+    registry: &'static Registry<T>,
+    tag: &'static str,
+    value_key: &'static str,
+    default_variant: Option<&'static str>,
+) -> Schema
+where
+    T: ?Sized + Family,
+{
+    one_of(impls(registry).map(|(name, schema)| {
+        let schema = schema(generator).to_value();
+        let internal = Internal {
+            tag,
+            name,
+            value_key,
+            required: default_variant != Some(name),
+        };
+        internal.tagged(generator, schema)
+    }))
+}
+
+struct Internal {
+    tag: &'static str,
+    name: &'static str,
+    value_key: &'static str,
+    required: bool,
+}
+
+impl Internal {
+    // Maps hold the tag among their own entries, unit values are the tag
+    // alone, unit variants are a map from their name to null, and every other
+    // value goes under the value key. Which of these the value is comes from
+    // the definition that the schema refers to, and for enums of different
+    // kinds of variants, from each of their variants.
+    fn tagged(&self, generator: &SchemaGenerator, schema: Value) -> Value {
+        let resolved = resolve(generator, &schema);
+        if resolved.get("type").is_none() {
+            for keyword in ["oneOf", "anyOf"] {
+                if let Some(Value::Array(variants)) = resolved.get(keyword) {
+                    let variants: Vec<Value> = variants
+                        .iter()
+                        .map(|variant| self.tagged(generator, variant.clone()))
+                        .collect();
+                    return json!({ keyword: variants });
+                }
+            }
+        }
+
+        if let Some(names) = unit_variants(resolved) {
+            let mut variants: Vec<Value> = names
+                .into_iter()
+                .map(|name| {
+                    self.with_tag(json!({
+                        "type": "object",
+                        "properties": {
+                            name: { "type": "null" },
+                        },
+                        "required": [name],
+                        "additionalProperties": false,
+                    }))
+                })
+                .collect();
+            return match variants.len() {
+                1 => variants.remove(0),
+                _ => json!({ "oneOf": variants }),
+            };
+        }
+
+        self.with_tag(match resolved.get("type").and_then(Value::as_str) {
+            Some("object") => resolved.clone(),
+            Some("null") => json!({ "type": "object" }),
+            _ => json!({
+                "type": "object",
+                "properties": {
+                    self.value_key: schema,
+                },
+                "required": [self.value_key],
+            }),
+        })
+    }
+
+    fn with_tag(&self, mut object: Value) -> Value {
+        object["properties"][self.tag] = json!({ "const": self.name });
+        if self.required {
+            match &mut object["required"] {
+                Value::Array(required) => required.push(Value::from(self.tag)),
+                required => *required = json!([self.tag]),
+            }
+        }
+        object
+    }
+}
+
+// Names of the unit variants that schemars describes as the strings they
+// serialize as in other representations.
+fn unit_variants(schema: &Value) -> Option<Vec<&str>> {
+    if schema.get("type").and_then(Value::as_str) != Some("string") {
+        return None;
+    }
+    match (schema.get("const"), schema.get("enum")) {
+        (Some(Value::String(name)), _) => Some(vec![name.as_str()]),
+        (None, Some(Value::Array(names))) => names.iter().map(Value::as_str).collect(),
+        _ => None,
+    }
+}
+
+// The definition that a `$ref` schema refers to among those of the generator,
+// or the schema itself if it is not a reference to one.
+fn resolve<'a>(generator: &'a SchemaGenerator, schema: &'a Value) -> &'a Value {
+    let definitions_path = &generator.settings().definitions_path;
+    let definitions_path = definitions_path
+        .strip_prefix('#')
+        .unwrap_or(definitions_path);
+    let definitions_path = definitions_path
+        .strip_suffix('/')
+        .unwrap_or(definitions_path);
+    let definition = schema
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| pointer.strip_prefix(definitions_path))
+        .and_then(|pointer| pointer.strip_prefix('/'))
+        .and_then(decode_ref_name)
+        .and_then(|name| generator.definitions().get(&name));
+    match definition {
+        Some(definition) => resolve(generator, definition),
+        None => schema,
+    }
+}
+
+// Reverses the JSON Pointer and percent encoding of the name of a definition
+// in a `$ref`.
+fn decode_ref_name(encoded: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        rest = tail;
+        let decoded = match byte {
+            b'~' => {
+                let (&escape, tail) = rest.split_first()?;
+                rest = tail;
+                match escape {
+                    b'0' => b'~',
+                    b'1' => b'/',
+                    _ => return None,
+                }
+            }
+            b'%' => {
+                let hex = core::str::from_utf8(rest.get(..2)?).ok()?;
+                rest = &rest[2..];
+                u8::from_str_radix(hex, 16).ok()?
+            }
+            byte => byte,
+        };
+        bytes.push(decoded);
+    }
+    String::from_utf8(bytes).ok()
+}
+
+pub fn adjacently<T>(
+    generator: &mut SchemaGenerator,
+    registry: &'static Registry<T>,
+    fields: &'static [&'static str; 2], // [tag, content]
+    default_variant: Option<&'static str>,
+) -> Schema
+where
+    T: ?Sized + Family,
+{
+    let [tag, content] = *fields;
+    one_of(impls(registry).map(|(name, schema)| {
+        let required = if default_variant == Some(name) {
+            json!([content])
+        } else {
+            json!([tag, content])
+        };
+        json!({
+            "type": "object",
+            "properties": {
+                tag: { "const": name },
+                content: schema(generator),
+            },
+            "required": required,
+        })
+    }))
+}
+
+// Name and schema of every impl that unambiguously owns its name.
+fn impls<T>(registry: &'static Registry<T>) -> impl Iterator<Item = (&'static str, SchemaFn)>
+where
+    T: ?Sized + Family,
+{
+    registry.names.iter().filter_map(move |name| {
+        let deserialize_fn = registry.map.get(name)?.as_ref()?;
+        Some((*name, deserialize_fn.schema?))
+    })
+}
+
+fn one_of<I>(variants: I) -> Schema
+where
+    I: Iterator<Item = Value>,
+{
+    let variants: Vec<Value> = variants.collect();
+    json_schema!({ "oneOf": variants })
+}
@@ -0,0 +1,111 @@
+use crate::private::{Context, Seeded};
+use alloc::boxed::Box;
+use serde::de::{DeserializeSeed, Deserializer};
+
+/// Deserializes a trait object with the context of its trait's seed.
+///
+/// A trait declared with `#[typetag::serde(seed = Context)]` has no
+/// `Deserialize` impl for `Box<dyn Trait>`. Its trait objects deserialize
+/// through `Seed::new(&context)` instead, which hands the context to the
+/// [`DeserializeWith`] impl of whichever type the tag selects.
+///
+/// ```
+/// use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+/// use serde::Serialize;
+/// use typetag::DeserializeWith;
+///
+/// struct Units {
+///     per_meter: f64,
+/// }
+///
+/// #[typetag::serde(seed = Units)]
+/// trait Quantity {
+///     fn meters(&self) -> f64;
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Length(f64);
+///
+/// struct LengthSeed<'a>(&'a Units);
+///
+/// impl<'de, 'a> DeserializeSeed<'de> for LengthSeed<'a> {
+///     type Value = Length;
+///
+///     fn deserialize<D>(self, deserializer: D) -> Result<Length, D::Error>
+///     where
+///         D: Deserializer<'de>,
+///     {
+///         let value = f64::deserialize(deserializer)?;
+///         Ok(Length(value / self.0.per_meter))
+///     }
+/// }
+///
+/// impl<'a> DeserializeWith<'a, Units> for Length {
+///     type Seed = LengthSeed<'a>;
+///
+///     fn seed(units: &'a Units) -> LengthSeed<'a> {
+///         LengthSeed(units)
+///     }
+/// }
+///
+/// #[typetag::serde]
+/// impl Quantity for Length {
+///     fn meters(&self) -> f64 {
+///         self.0
+///     }
+/// }
+/// #
+/// # #[cfg(feature = "runtime-registration")]
+/// # typetag::register::<dyn Quantity, Length>("Length");
+///
+/// let centimeters = Units { per_meter: 100.0 };
+/// let mut deserializer = serde_json::Deserializer::from_str(r#"{"Length":250}"#);
+/// let quantity = typetag::Seed::<dyn Quantity>::new(&centimeters)
+///     .deserialize(&mut deserializer)
+///     .unwrap();
+/// assert_eq!(quantity.meters(), 2.5);
+/// ```
+pub struct Seed<'ctx, T: ?Sized + Seeded> {
+    context: &'ctx Context<'ctx, T>,
+}
+
+impl<'ctx, T: ?Sized + Seeded> Seed<'ctx, T> {
+    /// Seed that deserializes `Box<T>` with the given context.
+    pub fn new(context: &'ctx Context<'ctx, T>) -> Self {
+        Seed { context }
+    }
+}
+
+impl<'ctx, T: ?Sized + Seeded> Copy for Seed<'ctx, T> {}
+
+impl<'ctx, T: ?Sized + Seeded> Clone for Seed<'ctx, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'de, 'ctx, T: ?Sized + Seeded> DeserializeSeed<'de> for Seed<'ctx, T> {
+    type Value = Box<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_seeded(self.context, deserializer)
+    }
+}
+
+/// Deserialization of an impl of a trait declared with
+/// `#[typetag::serde(seed = C)]`.
+///
+/// Impls of such a trait implement this in place of `Deserialize`, building the
+/// seed they deserialize with from the context given to [`Seed::new`]. For a
+/// context that borrows, as in `seed = Interner<'_>`, implement
+/// `DeserializeWith<'a, Interner<'a>>` for every `'a`.
+pub trait DeserializeWith<'a, C>: Sized {
+    /// Seed that deserializes `Self`.
+    type Seed: for<'de> DeserializeSeed<'de, Value = Self>;
+
+    /// Builds the seed from the context.
+    fn seed(context: &'a C) -> Self::Seed;
+}
@@ -1,4 +1,3 @@
-use crate::internally::DEFAULT_KEY;
 use crate::is_serialize_str::expect_str;
 use alloc::borrow::ToOwned;
 use alloc::boxed::Box;
@@ -25,9 +24,39 @@ where
     }
 }
 
+// Tag of the impl being serialized: its name, or its numeric id in formats that
+// are not human readable if the trait uses ids.
+#[derive(Copy, Clone)]
+pub struct Variant {
+    pub name: &'static str,
+    pub id: Option<u64>,
+}
+
+impl Variant {
+    pub fn new(name: &'static str, id: Option<u64>, is_human_readable: bool) -> Self {
+        Variant {
+            name,
+            id: if is_human_readable { None } else { id },
+        }
+    }
+}
+
+impl Serialize for Variant {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.id {
+            Some(id) => serializer.serialize_u64(id),
+            None => serializer.serialize_str(self.name),
+        }
+    }
+}
+
 pub struct InternallyTaggedSerializer<S> {
     pub tag: &'static str,
-    pub variant: &'static str,
+    pub variant: Variant,
+    pub value_key: &'static str,
     pub delegate: S,
 }
 
@@ -40,8 +69,8 @@ where
         T: ?Sized + Serialize,
     {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
-        map.serialize_entry(DEFAULT_KEY, value)?;
+        map.serialize_entry(self.tag, &self.variant)?;
+        map.serialize_entry(self.value_key, value)?;
         map.end()
     }
 }
@@ -138,7 +167,7 @@ where
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
         let mut map = self.delegate.serialize_map(Some(1))?;
-        map.serialize_entry(self.tag, self.variant)?;
+        map.serialize_entry(self.tag, &self.variant)?;
         map.end()
     }
 
@@ -153,7 +182,7 @@ where
         inner_variant: &'static str,
     ) -> Result<Self::Ok, Self::Error> {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
+        map.serialize_entry(self.tag, &self.variant)?;
         map.serialize_entry(inner_variant, &())?;
         map.end()
     }
@@ -180,22 +209,22 @@ where
         T: ?Sized + Serialize,
     {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
+        map.serialize_entry(self.tag, &self.variant)?;
         map.serialize_entry(inner_variant, inner_value)?;
         map.end()
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
-        map.serialize_key(DEFAULT_KEY)?;
+        map.serialize_entry(self.tag, &self.variant)?;
+        map.serialize_key(self.value_key)?;
         Ok(SerializeSeqAsMapValue::new(map, len))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
-        map.serialize_key(DEFAULT_KEY)?;
+        map.serialize_entry(self.tag, &self.variant)?;
+        map.serialize_key(self.value_key)?;
         Ok(SerializeTupleAsMapValue::new(map, len))
     }
 
@@ -205,8 +234,8 @@ where
         len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
-        map.serialize_key(DEFAULT_KEY)?;
+        map.serialize_entry(self.tag, &self.variant)?;
+        map.serialize_key(self.value_key)?;
         Ok(SerializeTupleStructAsMapValue::new(map, name, len))
     }
 
@@ -218,14 +247,14 @@ where
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
+        map.serialize_entry(self.tag, &self.variant)?;
         map.serialize_key(name)?;
         Ok(SerializeTupleStructAsMapValue::new(map, name, len))
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
         let mut map = self.delegate.serialize_map(len.map(|len| len + 1))?;
-        map.serialize_entry(self.tag, self.variant)?;
+        map.serialize_entry(self.tag, &self.variant)?;
         Ok(map)
     }
 
@@ -235,8 +264,12 @@ where
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
         let mut state = self.delegate.serialize_map(Some(len + 1))?;
-        state.serialize_entry(self.tag, self.variant)?;
-        Ok(SerializeStructAsMap::new(state, self.tag, self.variant))
+        state.serialize_entry(self.tag, &self.variant)?;
+        Ok(SerializeStructAsMap::new(
+            state,
+            self.tag,
+            self.variant.name,
+        ))
     }
 
     fn serialize_struct_variant(
@@ -247,7 +280,7 @@ where
         len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
         let mut map = self.delegate.serialize_map(Some(2))?;
-        map.serialize_entry(self.tag, self.variant)?;
+        map.serialize_entry(self.tag, &self.variant)?;
         map.serialize_key(name)?;
         Ok(SerializeStructVariantAsMapValue::new(map, name, len))
     }
@@ -0,0 +1,362 @@
+use crate::ser::{Content, ContentSerializer, Wrap};
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+use core::hash::Hasher;
+use core::slice;
+use serde::Serialize;
+
+// Used from the impls generated by `derive(...)` on a trait. Trait objects are
+// formatted, compared and hashed by the tag of their impl along with its
+// serialized content, in a canonical form in which map entries are ordered and
+// floats of equal value have equal bits. Comparing or hashing a value that fails
+// to serialize panics, as no answer would keep `Eq` reflexive. The same goes for
+// an unlisted instantiation of a generic impl, which has no tag.
+
+pub fn debug<T>(name: Option<&str>, value: &T, formatter: &mut fmt::Formatter) -> fmt::Result
+where
+    T: ?Sized + erased_serde::Serialize,
+{
+    let name = name.ok_or(fmt::Error)?;
+    let content = match canonical(value) {
+        Ok(content) => content,
+        Err(_) => return Err(fmt::Error),
+    };
+    // The impl is named by its tag in place of its type name.
+    match &content {
+        Content::Unit | Content::UnitStruct(_) => formatter.write_str(name),
+        Content::NewtypeStruct(_, content) => {
+            render_tuple(name, slice::from_ref(&**content), formatter)
+        }
+        Content::TupleStruct(_, elements) => render_tuple(name, elements, formatter),
+        Content::Struct(_, fields) => render_struct(name, fields, formatter),
+        content => render_tuple(name, slice::from_ref(content), formatter),
+    }
+}
+
+pub fn eq<T, U>(name: Option<&str>, value: &T, other_name: Option<&str>, other: &U) -> bool
+where
+    T: ?Sized + erased_serde::Serialize,
+    U: ?Sized + erased_serde::Serialize,
+{
+    encoded(name, value) == encoded(other_name, other)
+}
+
+pub fn hash<T, H>(name: Option<&str>, value: &T, state: &mut H)
+where
+    T: ?Sized + erased_serde::Serialize,
+    H: Hasher,
+{
+    state.write(&encoded(name, value));
+}
+
+fn canonical<T>(value: &T) -> Result<Content, erased_serde::Error>
+where
+    T: ?Sized + erased_serde::Serialize,
+{
+    let mut content = Wrap(value).serialize(ContentSerializer::<erased_serde::Error>::new())?;
+    canonicalize(&mut content);
+    Ok(content)
+}
+
+fn encoded<T>(name: Option<&str>, value: &T) -> Vec<u8>
+where
+    T: ?Sized + erased_serde::Serialize,
+{
+    let name = match name {
+        Some(name) => name,
+        None => panic!("unlisted instantiation of a generic impl has no tag for comparison"),
+    };
+    let content = match canonical(value) {
+        Ok(content) => content,
+        Err(error) => panic!("failed to serialize {} for comparison: {}", name, error),
+    };
+    let mut bytes = Vec::new();
+    encode_str(name, &mut bytes);
+    encode(&content, &mut bytes);
+    bytes
+}
+
+fn canonicalize(content: &mut Content) {
+    match content {
+        // Negative zero equals zero, and every NaN is the same NaN.
+        Content::F32(v) => {
+            if *v == 0.0 {
+                *v = 0.0;
+            } else if v.is_nan() {
+                *v = f32::NAN;
+            }
+        }
+        Content::F64(v) => {
+            if *v == 0.0 {
+                *v = 0.0;
+            } else if v.is_nan() {
+                *v = f64::NAN;
+            }
+        }
+        Content::Some(content)
+        | Content::NewtypeStruct(_, content)
+        | Content::NewtypeVariant(_, _, _, content) => canonicalize(content),
+        Content::Seq(elements)
+        | Content::Tuple(elements)
+        | Content::TupleStruct(_, elements)
+        | Content::TupleVariant(_, _, _, elements) => {
+            for element in elements {
+                canonicalize(element);
+            }
+        }
+        Content::Map(entries) => {
+            for (key, value) in entries.iter_mut() {
+                canonicalize(key);
+                canonicalize(value);
+            }
+            entries.sort_by_cached_key(|(key, _value)| {
+                let mut bytes = Vec::new();
+                encode(key, &mut bytes);
+                bytes
+            });
+        }
+        Content::Struct(_, fields) | Content::StructVariant(_, _, _, fields) => {
+            for (_key, value) in fields {
+                canonicalize(value);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Encoding in which two contents are equal exactly if their bytes are, with a
+// distinct leading byte for each kind of content and lengths ahead of every
+// variable-length part.
+fn encode(content: &Content, bytes: &mut Vec<u8>) {
+    match content {
+        Content::Bool(v) => {
+            bytes.push(0);
+            bytes.push(u8::from(*v));
+        }
+        Content::U8(v) => {
+            bytes.push(1);
+            bytes.push(*v);
+        }
+        Content::U16(v) => {
+            bytes.push(2);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::U32(v) => {
+            bytes.push(3);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::U64(v) => {
+            bytes.push(4);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::U128(v) => {
+            bytes.push(5);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::I8(v) => {
+            bytes.push(6);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::I16(v) => {
+            bytes.push(7);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::I32(v) => {
+            bytes.push(8);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::I64(v) => {
+            bytes.push(9);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::I128(v) => {
+            bytes.push(10);
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        Content::F32(v) => {
+            bytes.push(11);
+            bytes.extend_from_slice(&v.to_bits().to_le_bytes());
+        }
+        Content::F64(v) => {
+            bytes.push(12);
+            bytes.extend_from_slice(&v.to_bits().to_le_bytes());
+        }
+        Content::Char(v) => {
+            bytes.push(13);
+            bytes.extend_from_slice(&u32::from(*v).to_le_bytes());
+        }
+        Content::String(v) => {
+            bytes.push(14);
+            encode_str(v, bytes);
+        }
+        Content::Bytes(v) => {
+            bytes.push(15);
+            encode_len(v.len(), bytes);
+            bytes.extend_from_slice(v);
+        }
+        Content::None => bytes.push(16),
+        Content::Some(content) => {
+            bytes.push(17);
+            encode(content, bytes);
+        }
+        Content::Unit => bytes.push(18),
+        Content::UnitStruct(name) => {
+            bytes.push(19);
+            encode_str(name, bytes);
+        }
+        Content::UnitVariant(name, _index, variant) => {
+            bytes.push(20);
+            encode_str(name, bytes);
+            encode_str(variant, bytes);
+        }
+        Content::NewtypeStruct(name, content) => {
+            bytes.push(21);
+            encode_str(name, bytes);
+            encode(content, bytes);
+        }
+        Content::NewtypeVariant(name, _index, variant, content) => {
+            bytes.push(22);
+            encode_str(name, bytes);
+            encode_str(variant, bytes);
+            encode(content, bytes);
+        }
+        Content::Seq(elements) => {
+            bytes.push(23);
+            encode_seq(elements, bytes);
+        }
+        Content::Tuple(elements) => {
+            bytes.push(24);
+            encode_seq(elements, bytes);
+        }
+        Content::TupleStruct(name, elements) => {
+            bytes.push(25);
+            encode_str(name, bytes);
+            encode_seq(elements, bytes);
+        }
+        Content::TupleVariant(name, _index, variant, elements) => {
+            bytes.push(26);
+            encode_str(name, bytes);
+            encode_str(variant, bytes);
+            encode_seq(elements, bytes);
+        }
+        Content::Map(entries) => {
+            bytes.push(27);
+            encode_len(entries.len(), bytes);
+            for (key, value) in entries {
+                encode(key, bytes);
+                encode(value, bytes);
+            }
+        }
+        Content::Struct(name, fields) => {
+            bytes.push(28);
+            encode_str(name, bytes);
+            encode_fields(fields, bytes);
+        }
+        Content::StructVariant(name, _index, variant, fields) => {
+            bytes.push(29);
+            encode_str(name, bytes);
+            encode_str(variant, bytes);
+            encode_fields(fields, bytes);
+        }
+    }
+}
+
+fn encode_len(len: usize, bytes: &mut Vec<u8>) {
+    bytes.extend_from_slice(&(len as u64).to_le_bytes());
+}
+
+fn encode_str(string: &str, bytes: &mut Vec<u8>) {
+    encode_len(string.len(), bytes);
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+fn encode_seq(elements: &[Content], bytes: &mut Vec<u8>) {
+    encode_len(elements.len(), bytes);
+    for element in elements {
+        encode(element, bytes);
+    }
+}
+
+fn encode_fields(fields: &[(&'static str, Content)], bytes: &mut Vec<u8>) {
+    encode_len(fields.len(), bytes);
+    for (key, value) in fields {
+        encode_str(key, bytes);
+        encode(value, bytes);
+    }
+}
+
+// Content formatted the way `#[derive(Debug)]` would format the value it was
+// serialized from.
+struct Render<'a>(&'a Content);
+
+impl<'a> Debug for Render<'a> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Content::Bool(v) => Debug::fmt(v, formatter),
+            Content::U8(v) => Debug::fmt(v, formatter),
+            Content::U16(v) => Debug::fmt(v, formatter),
+            Content::U32(v) => Debug::fmt(v, formatter),
+            Content::U64(v) => Debug::fmt(v, formatter),
+            Content::U128(v) => Debug::fmt(v, formatter),
+            Content::I8(v) => Debug::fmt(v, formatter),
+            Content::I16(v) => Debug::fmt(v, formatter),
+            Content::I32(v) => Debug::fmt(v, formatter),
+            Content::I64(v) => Debug::fmt(v, formatter),
+            Content::I128(v) => Debug::fmt(v, formatter),
+            Content::F32(v) => Debug::fmt(v, formatter),
+            Content::F64(v) => Debug::fmt(v, formatter),
+            Content::Char(v) => Debug::fmt(v, formatter),
+            Content::String(v) => Debug::fmt(v, formatter),
+            Content::Bytes(v) => Debug::fmt(v, formatter),
+            Content::None => formatter.write_str("None"),
+            Content::Some(content) => render_tuple("Some", slice::from_ref(&**content), formatter),
+            Content::Unit => formatter.write_str("()"),
+            Content::UnitStruct(name) | Content::UnitVariant(_, _, name) => {
+                formatter.write_str(name)
+            }
+            Content::NewtypeStruct(name, content)
+            | Content::NewtypeVariant(_, _, name, content) => {
+                render_tuple(name, slice::from_ref(&**content), formatter)
+            }
+            Content::Seq(elements) => formatter
+                .debug_list()
+                .entries(elements.iter().map(Render))
+                .finish(),
+            Content::Tuple(elements) => render_tuple("", elements, formatter),
+            Content::TupleStruct(name, elements) | Content::TupleVariant(_, _, name, elements) => {
+                render_tuple(name, elements, formatter)
+            }
+            Content::Map(entries) => formatter
+                .debug_map()
+                .entries(
+                    entries
+                        .iter()
+                        .map(|(key, value)| (Render(key), Render(value))),
+                )
+                .finish(),
+            Content::Struct(name, fields) | Content::StructVariant(_, _, name, fields) => {
+                render_struct(name, fields, formatter)
+            }
+        }
+    }
+}
+
+fn render_tuple(name: &str, elements: &[Content], formatter: &mut fmt::Formatter) -> fmt::Result {
+    let mut debug = formatter.debug_tuple(name);
+    for element in elements {
+        debug.field(&Render(element));
+    }
+    debug.finish()
+}
+
+fn render_struct(
+    name: &str,
+    fields: &[(&'static str, Content)],
+    formatter: &mut fmt::Formatter,
+) -> fmt::Result {
+    let mut debug = formatter.debug_struct(name);
+    for (key, value) in fields {
+        debug.field(key, &Render(value));
+    }
+    debug.finish()
+}
@@ -0,0 +1,181 @@
+use crate::content::{Content, ContentDeserializer};
+use crate::de::FnApply;
+use crate::private::{Context, Family, Object, Registry};
+use alloc::boxed::Box;
+use core::fmt;
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer, IntoDeserializer, SeqAccess, Visitor,
+};
+use serde::ser::{Serialize, Serializer};
+
+/// Data of a trait object whose tag has no registered impl.
+///
+/// A trait declared with `#[typetag::serde(tag = "type", unknown = "Unknown")]`
+/// deserializes every tag it does not know into the impl registered as
+/// `"Unknown"`, which may be this type itself or a newtype around it. The data
+/// is kept as it was read so that serializing the trait object reproduces the
+/// original payload, tag included, in the same representation.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[typetag::serde(tag = "type", unknown = "Unknown")]
+/// trait WebEvent {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct PageLoad;
+///
+/// #[typetag::serde]
+/// impl WebEvent for PageLoad {}
+///
+/// #[typetag::serde(name = "Unknown")]
+/// impl WebEvent for typetag::Unknown {}
+/// #
+/// # #[cfg(feature = "runtime-registration")]
+/// # {
+/// #     typetag::register::<dyn WebEvent, PageLoad>("PageLoad");
+/// #     typetag::register::<dyn WebEvent, typetag::Unknown>("Unknown");
+/// # }
+///
+/// let json = r#"{"type":"Scroll","dy":120}"#;
+/// let event: Box<dyn WebEvent> = serde_json::from_str(json).unwrap();
+/// assert_eq!(serde_json::to_string(&event).unwrap(), json);
+/// ```
+///
+/// Telling the tag apart from the rest of the payload needs the data buffered
+/// first, so a trait with an `unknown` impl only deserializes from
+/// self-describing formats.
+#[derive(Clone, Debug)]
+pub struct Unknown {
+    tag: Content<'static>,
+    content: Content<'static>,
+}
+
+impl Unknown {
+    /// The unrecognized tag, or `None` if it was a numeric id.
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_str()
+    }
+
+    /// The unrecognized numeric id, or `None` if the tag was a name.
+    pub fn id(&self) -> Option<u64> {
+        self.tag.as_u64()
+    }
+}
+
+impl Serialize for Unknown {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.content.serialize(serializer)
+    }
+}
+
+// Deserialized by typetag from the tag and the whole payload, which no input
+// holds on its own.
+#[doc(hidden)]
+impl<'de> Deserialize<'de> for Unknown {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(2, UnknownVisitor)
+    }
+}
+
+struct UnknownVisitor;
+
+impl<'de> Visitor<'de> for UnknownVisitor {
+    type Value = Unknown;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("the data of a tag with no registered impl")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let tag: Content = match seq.next_element()? {
+            Some(tag) => tag,
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+        let content: Content = match seq.next_element()? {
+            Some(content) => content,
+            None => return Err(de::Error::invalid_length(1, &self)),
+        };
+        Ok(Unknown {
+            tag: tag.into_owned(),
+            content: content.into_owned(),
+        })
+    }
+}
+
+// Where the tag lives in the buffered payload.
+#[derive(Copy, Clone)]
+pub enum Tagging {
+    External,
+    Internal(&'static str),
+    Adjacent(&'static str),
+}
+
+pub fn deserialize<'de, 'c, D, T, F>(
+    deserializer: D,
+    trait_object: &'static str,
+    tagging: Tagging,
+    registry: &'static Registry<T>,
+    context: &'c Context<'c, T>,
+    unknown: &'static str,
+    known: F,
+) -> Result<Box<Object<'de, T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ?Sized + Family,
+    F: FnOnce(ContentDeserializer<'de, D::Error>) -> Result<Box<Object<'de, T>>, D::Error>,
+{
+    let content = Content::deserialize(deserializer)?;
+
+    // The name of the unknown impl is not the tag of any data of its own, so
+    // input that carries it goes the way of every other unknown tag.
+    let tag = match find_tag(&content, tagging) {
+        Some(tag) if tag.as_str() == Some(unknown) || !is_registered(tag, registry) => tag.clone(),
+        _ => return known(content.into_deserializer()),
+    };
+
+    let deserialize_fn = match registry.map.get(unknown) {
+        Some(Some(deserialize_fn)) => *deserialize_fn,
+        _ => {
+            return Err(de::Error::custom(format_args!(
+                "unknown = {:?} of dyn {} is not a unique registered tag",
+                unknown, trait_object,
+            )));
+        }
+    };
+
+    let payload = Content::Seq(alloc::vec![tag, content]);
+    FnApply::new(deserialize_fn, context).deserialize(payload.into_deserializer())
+}
+
+fn find_tag<'a, 'de>(content: &'a Content<'de>, tagging: Tagging) -> Option<&'a Content<'de>> {
+    match (tagging, content) {
+        (Tagging::External, Content::Map(entries)) if entries.len() == 1 => Some(&entries[0].0),
+        (Tagging::Internal(field) | Tagging::Adjacent(field), Content::Map(entries)) => entries
+            .iter()
+            .find(|(key, _value)| key.as_str() == Some(field))
+            .map(|(_key, value)| value),
+        (Tagging::Adjacent(_), Content::Seq(elements)) => elements.first(),
+        _ => None,
+    }
+}
+
+fn is_registered<T: ?Sized + Family>(tag: &Content, registry: &Registry<T>) -> bool {
+    if let Some(name) = tag.as_str() {
+        registry.map.contains_key(name)
+    } else if let Some(id) = tag.as_u64() {
+        registry.ids.contains_key(&id)
+    } else {
+        // Not a tag at all, which the representation reports on its own.
+        true
+    }
+}
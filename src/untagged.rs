@@ -0,0 +1,53 @@
+use crate::content::Content;
+use crate::de::FnApply;
+use crate::private::{Context, Family, Object, Registry};
+use crate::ser::Wrap;
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::fmt::Write;
+use serde::de::{self, Deserialize, DeserializeSeed, Deserializer, IntoDeserializer};
+use serde::ser::{Serialize, Serializer};
+
+pub fn serialize<S, T>(serializer: S, concrete: &T) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ?Sized + erased_serde::Serialize,
+{
+    Wrap(concrete).serialize(serializer)
+}
+
+pub fn deserialize<'de, 'c, D, T>(
+    deserializer: D,
+    trait_object: &'static str,
+    registry: &'static Registry<T>,
+    context: &'c Context<'c, T>,
+) -> Result<Box<Object<'de, T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ?Sized + Family,
+{
+    let content = Content::deserialize(deserializer)?;
+
+    // Try each impl in order of its tag, so that the first one to accept the
+    // input wins regardless of link order.
+    let mut failures = String::new();
+    for name in &registry.names {
+        let deserialize_fn = match registry.map.get(name) {
+            Some(Some(deserialize_fn)) => *deserialize_fn,
+            _ => continue,
+        };
+        let content = content.clone().into_deserializer();
+        match (FnApply::new(deserialize_fn, context)).deserialize(content) {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                let err: D::Error = err;
+                let _ = write!(failures, "; {}: {}", name, err);
+            }
+        }
+    }
+
+    Err(de::Error::custom(format_args!(
+        "data did not match any impl of dyn {}{}",
+        trait_object, failures,
+    )))
+}
@@ -0,0 +1,171 @@
+use crate::private::Registered;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+/// Builds the registry of the trait object `T` and checks that no two impls
+/// registered the same tag.
+///
+/// A duplicate tag otherwise only surfaces as a "non-unique tag" error when
+/// deserializing that exact tag. Calling this at startup lets a program fail
+/// fast instead, with the conflicting impls listed in the error.
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+///
+/// #[typetag::serde]
+/// trait WebEvent {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct PageLoad;
+///
+/// #[typetag::serde]
+/// impl WebEvent for PageLoad {}
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct LegacyPageLoad;
+///
+/// #[typetag::serde(name = "PageLoad")]
+/// impl WebEvent for LegacyPageLoad {}
+/// #
+/// # #[cfg(feature = "runtime-registration")]
+/// # {
+/// #     typetag::register::<dyn WebEvent, PageLoad>("PageLoad");
+/// #     typetag::register::<dyn WebEvent, LegacyPageLoad>("PageLoad");
+/// # }
+///
+/// let error = typetag::validate::<dyn WebEvent>().unwrap_err();
+/// let duplicate = &error.duplicates()[0];
+/// assert_eq!(duplicate.tag(), "PageLoad");
+/// assert_eq!(duplicate.impls().len(), 2);
+/// ```
+pub fn validate<T>() -> Result<(), ValidationError>
+where
+    T: ?Sized + Registered,
+{
+    let registry = T::registry();
+    if registry.duplicates.is_empty() && registry.duplicate_ids.is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationError {
+            duplicates: &registry.duplicates,
+            duplicate_ids: &registry.duplicate_ids,
+        })
+    }
+}
+
+/// Error returned by [`validate`] when tags of a trait object are not unique.
+#[derive(Debug)]
+pub struct ValidationError {
+    duplicates: &'static [Duplicate],
+    duplicate_ids: &'static [DuplicateId],
+}
+
+impl ValidationError {
+    /// Every tag registered by more than one impl, in sorted order.
+    pub fn duplicates(&self) -> &'static [Duplicate] {
+        self.duplicates
+    }
+
+    /// Every numeric id registered by more than one impl, in sorted order.
+    pub fn duplicate_ids(&self) -> &'static [DuplicateId] {
+        self.duplicate_ids
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut separator = "";
+        if !self.duplicates.is_empty() {
+            formatter.write_str("non-unique tags:")?;
+            for (i, duplicate) in self.duplicates.iter().enumerate() {
+                if i > 0 {
+                    formatter.write_str(";")?;
+                }
+                write!(formatter, " {:?} registered by ", duplicate.tag)?;
+                write_impls(formatter, &duplicate.impls)?;
+            }
+            separator = "; ";
+        }
+        if !self.duplicate_ids.is_empty() {
+            write!(formatter, "{}non-unique ids:", separator)?;
+            for (i, duplicate) in self.duplicate_ids.iter().enumerate() {
+                if i > 0 {
+                    formatter.write_str(";")?;
+                }
+                write!(formatter, " {} registered by ", duplicate.id)?;
+                write_impls(formatter, &duplicate.impls)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_impls(formatter: &mut fmt::Formatter, impls: &[ImplInfo]) -> fmt::Result {
+    for (i, info) in impls.iter().enumerate() {
+        if i > 0 {
+            formatter.write_str(", ")?;
+        }
+        write!(formatter, "{} in {}", info.type_name, info.module_path)?;
+    }
+    Ok(())
+}
+
+/// A tag registered by more than one impl.
+#[derive(Debug)]
+pub struct Duplicate {
+    pub(crate) tag: &'static str,
+    pub(crate) impls: Vec<ImplInfo>,
+}
+
+impl Duplicate {
+    /// The tag, which is either the name or an alias of each impl.
+    pub fn tag(&self) -> &'static str {
+        self.tag
+    }
+
+    /// The conflicting impls, in registration order.
+    pub fn impls(&self) -> &[ImplInfo] {
+        &self.impls
+    }
+}
+
+/// A numeric id registered by more than one impl.
+#[derive(Debug)]
+pub struct DuplicateId {
+    pub(crate) id: u64,
+    pub(crate) impls: Vec<ImplInfo>,
+}
+
+impl DuplicateId {
+    /// The id, as given by `id = ...` on each impl or hashed from its name.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The conflicting impls, in registration order.
+    pub fn impls(&self) -> &[ImplInfo] {
+        &self.impls
+    }
+}
+
+/// Where an impl registered into a trait object comes from.
+#[derive(Copy, Clone, Debug)]
+pub struct ImplInfo {
+    pub(crate) type_name: &'static str,
+    pub(crate) module_path: &'static str,
+}
+
+impl ImplInfo {
+    /// The implementing type, as given by `core::any::type_name`.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// The module containing the impl block, as given by `module_path!`.
+    ///
+    /// With the `runtime-registration` feature this is the module defining
+    /// the implementing type instead.
+    pub fn module_path(&self) -> &'static str {
+        self.module_path
+    }
+}
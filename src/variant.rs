@@ -0,0 +1,90 @@
+use crate::de::{FnApply, MapLookupVisitor};
+use crate::private::{Context, DeserializeFn, Family, Object, Registry};
+use crate::ser::Wrap;
+use alloc::boxed::Box;
+use core::convert::TryFrom;
+use core::fmt;
+use serde::de::{DeserializeSeed, Deserializer, EnumAccess, VariantAccess, Visitor};
+use serde::ser::{self, Serializer};
+
+pub fn serialize<S, T>(
+    serializer: S,
+    trait_object: &'static str,
+    variant: &'static str,
+    id: u64,
+    concrete: &T,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ?Sized + erased_serde::Serialize,
+{
+    let variant_index = match u32::try_from(id) {
+        Ok(variant_index) => variant_index,
+        Err(_) => {
+            return Err(ser::Error::custom(format_args!(
+                "id of dyn {}: {} does not fit in a variant index",
+                trait_object, id
+            )));
+        }
+    };
+    serializer.serialize_newtype_variant(trait_object, variant_index, variant, &Wrap(concrete))
+}
+
+pub fn deserialize<'de, 'c, D, T>(
+    deserializer: D,
+    trait_object: &'static str,
+    registry: &'static Registry<T>,
+    context: &'c Context<'c, T>,
+) -> Result<Box<Object<'de, T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ?Sized + Family,
+{
+    let visitor = TaggedVisitor {
+        trait_object,
+        registry,
+        context,
+    };
+    deserializer.deserialize_enum(trait_object, &registry.names, visitor)
+}
+
+struct TaggedVisitor<'c, T: ?Sized + Family + 'static> {
+    trait_object: &'static str,
+    registry: &'static Registry<T>,
+    context: &'c Context<'c, T>,
+}
+
+impl<'de, 'c, T: ?Sized + Family> Visitor<'de> for TaggedVisitor<'c, T> {
+    type Value = Box<Object<'de, T>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "dyn {}", self.trait_object)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        let map_lookup = MapLookupVisitor {
+            expected: &self,
+            registry: self.registry,
+        };
+        let (deserialize_fn, variant) = data.variant_seed(VariantSeed(map_lookup))?;
+        variant.newtype_variant_seed(FnApply::new(deserialize_fn, self.context))
+    }
+}
+
+// Formats hand the variant over as an identifier, which is either the name or
+// the variant index.
+struct VariantSeed<'a, T: ?Sized + Family + 'static>(MapLookupVisitor<'a, T>);
+
+impl<'de, 'a, T: ?Sized + Family + 'static> DeserializeSeed<'de> for VariantSeed<'a, T> {
+    type Value = DeserializeFn<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(self.0)
+    }
+}
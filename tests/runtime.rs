@@ -0,0 +1,199 @@
+#![cfg(feature = "runtime-registration")]
+
+use serde::{Deserialize, Serialize};
+
+#[typetag::serde(tag = "type")]
+trait Trait {
+    fn assert_a_is_11(&self);
+}
+
+#[derive(Serialize, Deserialize)]
+struct A {
+    a: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+struct B {
+    b: u8,
+}
+
+#[typetag::serde]
+impl Trait for A {
+    fn assert_a_is_11(&self) {
+        assert_eq!(self.a, 11);
+    }
+}
+
+#[typetag::serde]
+impl Trait for B {
+    fn assert_a_is_11(&self) {
+        panic!("is not A");
+    }
+}
+
+#[typetag::serde]
+trait Marked: Send {}
+
+#[derive(Serialize, Deserialize)]
+struct C;
+
+#[typetag::serde]
+impl Marked for C {}
+
+#[typetag::serde]
+trait Unregistered {}
+
+#[typetag::serde]
+impl Unregistered for C {}
+
+#[test]
+fn test_register() {
+    typetag::register::<dyn Trait, A>("A");
+    typetag::register::<dyn Trait, B>("B");
+
+    let json = r#"{"type":"A","a":11}"#;
+    let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+    trait_object.assert_a_is_11();
+    assert_eq!(typetag::registered_names::<dyn Trait>(), ["A", "B"]);
+
+    let json = r#"{"type":"B","b":11}"#;
+    let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+    assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+}
+
+#[typetag::serde]
+trait Aliased {}
+
+#[typetag::serde(alias = "Old")]
+impl Aliased for C {}
+
+#[test]
+fn test_register_with_aliases() {
+    typetag::register_with_aliases::<dyn Aliased, C>("C", &["Old"]);
+
+    let json = r#"{"Old":null}"#;
+    let trait_object: Box<dyn Aliased> = serde_json::from_str(json).unwrap();
+    assert_eq!(
+        serde_json::to_string(&trait_object).unwrap(),
+        r#"{"C":null}"#
+    );
+    assert_eq!(typetag::registered_names::<dyn Aliased>(), ["C"]);
+}
+
+#[typetag::serde]
+trait Validated {}
+
+#[typetag::serde]
+impl Validated for A {}
+
+#[typetag::serde(name = "A")]
+impl Validated for B {}
+
+#[typetag::serde]
+trait Late {}
+
+#[typetag::serde]
+impl Late for A {}
+
+#[typetag::serde]
+impl Late for B {}
+
+#[test]
+#[should_panic = "runtime::B registered into dyn runtime::Late after its registry was first used"]
+fn test_register_after_use() {
+    typetag::register::<dyn Late, A>("A");
+    typetag::validate::<dyn Late>().unwrap();
+    typetag::register::<dyn Late, B>("B");
+}
+
+#[test]
+fn test_validate() {
+    typetag::register::<dyn Validated, A>("A");
+    typetag::register::<dyn Validated, B>("A");
+    let error = typetag::validate::<dyn Validated>().unwrap_err();
+    let impls = error.duplicates()[0].impls();
+    assert_eq!(impls[0].type_name(), "runtime::A");
+    assert_eq!(impls[1].type_name(), "runtime::B");
+    assert_eq!(impls[1].module_path(), "runtime");
+}
+
+#[test]
+fn test_unregistered() {
+    let json = r#"{"C":null}"#;
+    let error = serde_json::from_str::<Box<dyn Unregistered>>(json)
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "unknown variant `C`, there are no variants at line 1 column 4",
+    );
+}
+
+#[test]
+fn test_marker_traits() {
+    typetag::register::<dyn Marked + Send, C>("C2");
+
+    let json = r#"{"C2":null}"#;
+    let _trait_object: Box<dyn Marked + Send> = serde_json::from_str(json).unwrap();
+}
+
+#[typetag::serde(tag_kind = "id")]
+trait Numbered {}
+
+#[typetag::serde(id = 3)]
+impl Numbered for C {}
+
+#[test]
+fn test_register_with_id() {
+    typetag::register::<dyn Numbered, C>("C");
+
+    let trait_object = &C as &dyn Numbered;
+    let bytes = postcard::to_stdvec(trait_object).unwrap();
+    assert_eq!(bytes, [1, 3]);
+    let trait_object: Box<dyn Numbered> = postcard::from_bytes(&bytes).unwrap();
+    assert_eq!(
+        serde_json::to_string(&trait_object).unwrap(),
+        r#"{"C":null}"#
+    );
+}
+
+#[cfg(feature = "schemars")]
+mod schema {
+    use super::C;
+    use schemars::{json_schema, schema_for, JsonSchema, Schema, SchemaGenerator};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    #[typetag::serde(tag = "type", schema)]
+    trait Described {}
+
+    impl JsonSchema for C {
+        fn schema_name() -> Cow<'static, str> {
+            Cow::Borrowed("C")
+        }
+
+        fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+            json_schema!({ "type": "null" })
+        }
+    }
+
+    #[typetag::serde]
+    impl Described for C {}
+
+    #[test]
+    fn test_register_schema() {
+        typetag::register::<dyn Described, C>("C");
+
+        let schema = schema_for!(Box<dyn Described>);
+        assert_eq!(
+            schema.get("oneOf"),
+            Some(&json!([{
+                "type": "object",
+                "properties": {
+                    "type": { "const": "C" },
+                },
+                "required": ["type"],
+            }])),
+        );
+    }
+}
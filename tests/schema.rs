@@ -0,0 +1,400 @@
+#![cfg(all(feature = "schemars", not(feature = "runtime-registration")))]
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::borrow::Cow;
+
+#[derive(Serialize, Deserialize)]
+struct Circle {
+    radius: f64,
+}
+
+impl JsonSchema for Circle {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::Borrowed("Circle")
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "properties": {
+                "radius": { "type": "number" },
+            },
+            "required": ["radius"],
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Label(String);
+
+impl JsonSchema for Label {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::Borrowed("Label")
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "string" })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Empty;
+
+impl JsonSchema for Empty {
+    fn schema_name() -> Cow<'static, str> {
+        Cow::Borrowed("Empty")
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({ "type": "null" })
+    }
+}
+
+mod externally_tagged {
+    use super::{Circle, Label};
+    use schemars::schema_for;
+    use serde_json::json;
+
+    #[typetag::serde(schema)]
+    trait Shape {}
+
+    #[typetag::serde]
+    impl Shape for Circle {}
+
+    #[typetag::serde]
+    impl Shape for Label {}
+
+    #[test]
+    fn test_schema() {
+        let schema = schema_for!(Box<dyn Shape>);
+        let expected = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "Shape",
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {
+                        "Circle": { "$ref": "#/$defs/Circle" },
+                    },
+                    "required": ["Circle"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Label": { "$ref": "#/$defs/Label" },
+                    },
+                    "required": ["Label"],
+                    "additionalProperties": false,
+                },
+            ],
+            "$defs": {
+                "Circle": {
+                    "type": "object",
+                    "properties": {
+                        "radius": { "type": "number" },
+                    },
+                    "required": ["radius"],
+                },
+                "Label": { "type": "string" },
+            },
+        });
+        assert_eq!(schema.to_value(), expected);
+    }
+}
+
+mod internally_tagged {
+    use super::{Circle, Empty, Label};
+    use schemars::schema_for;
+    use serde_json::json;
+
+    #[typetag::serde(tag = "type", default_variant = "Empty", schema)]
+    trait Shape: Send {}
+
+    #[typetag::serde]
+    impl Shape for Circle {}
+
+    #[typetag::serde]
+    impl Shape for Empty {}
+
+    #[typetag::serde]
+    impl Shape for Label {}
+
+    #[test]
+    fn test_schema() {
+        let schema = schema_for!(Box<dyn Shape + Send>);
+        let expected = json!([
+            {
+                "type": "object",
+                "properties": {
+                    "radius": { "type": "number" },
+                    "type": { "const": "Circle" },
+                },
+                "required": ["radius", "type"],
+            },
+            {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "Empty" },
+                },
+            },
+            {
+                "type": "object",
+                "properties": {
+                    "value": { "$ref": "#/$defs/Label" },
+                    "type": { "const": "Label" },
+                },
+                "required": ["value", "type"],
+            },
+        ]);
+        assert_eq!(schema.get("oneOf"), Some(&expected));
+    }
+}
+
+mod enum_payload {
+    use schemars::{json_schema, schema_for, JsonSchema, Schema, SchemaGenerator};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    #[typetag::serde(tag = "type", schema)]
+    trait Style {}
+
+    #[derive(Serialize, Deserialize)]
+    enum Fill {
+        Transparent,
+        Solid(String),
+        Gradient { from: String, to: String },
+    }
+
+    // As derived by schemars, which describes the enum by reference.
+    impl JsonSchema for Fill {
+        fn schema_name() -> Cow<'static, str> {
+            Cow::Borrowed("Fill")
+        }
+
+        fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+            json_schema!({
+                "oneOf": [
+                    { "type": "string", "enum": ["Transparent"] },
+                    {
+                        "type": "object",
+                        "properties": { "Solid": { "type": "string" } },
+                        "additionalProperties": false,
+                        "required": ["Solid"],
+                    },
+                    {
+                        "type": "object",
+                        "properties": {
+                            "Gradient": {
+                                "type": "object",
+                                "properties": {
+                                    "from": { "type": "string" },
+                                    "to": { "type": "string" },
+                                },
+                                "required": ["from", "to"],
+                            },
+                        },
+                        "additionalProperties": false,
+                        "required": ["Gradient"],
+                    },
+                ],
+            })
+        }
+    }
+
+    #[typetag::serde]
+    impl Style for Fill {}
+
+    #[test]
+    fn test_schema() {
+        let schema = schema_for!(Box<dyn Style>);
+        let expected = json!([{
+            "oneOf": [
+                {
+                    "type": "object",
+                    "properties": {
+                        "Transparent": { "type": "null" },
+                        "type": { "const": "Fill" },
+                    },
+                    "required": ["Transparent", "type"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Solid": { "type": "string" },
+                        "type": { "const": "Fill" },
+                    },
+                    "required": ["Solid", "type"],
+                    "additionalProperties": false,
+                },
+                {
+                    "type": "object",
+                    "properties": {
+                        "Gradient": {
+                            "type": "object",
+                            "properties": {
+                                "from": { "type": "string" },
+                                "to": { "type": "string" },
+                            },
+                            "required": ["from", "to"],
+                        },
+                        "type": { "const": "Fill" },
+                    },
+                    "required": ["Gradient", "type"],
+                    "additionalProperties": false,
+                },
+            ],
+        }]);
+        assert_eq!(schema.get("oneOf"), Some(&expected));
+
+        let fill = Fill::Gradient {
+            from: "red".to_owned(),
+            to: "blue".to_owned(),
+        };
+        let json = serde_json::to_string(&fill as &dyn Style).unwrap();
+        assert_eq!(
+            json,
+            r#"{"type":"Fill","Gradient":{"from":"red","to":"blue"}}"#,
+        );
+        let json = serde_json::to_string(&Fill::Transparent as &dyn Style).unwrap();
+        assert_eq!(json, r#"{"type":"Fill","Transparent":null}"#);
+    }
+}
+
+mod adjacently_tagged {
+    use super::{Circle, Empty};
+    use schemars::schema_for;
+    use serde_json::json;
+
+    #[typetag::serde(tag = "type", content = "data", schema)]
+    trait Shape {}
+
+    #[typetag::serde]
+    impl Shape for Circle {}
+
+    #[typetag::serde]
+    impl Shape for Empty {}
+
+    #[test]
+    fn test_schema() {
+        let schema = schema_for!(Box<dyn Shape>);
+        let expected = json!([
+            {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "Circle" },
+                    "data": { "$ref": "#/$defs/Circle" },
+                },
+                "required": ["type", "data"],
+            },
+            {
+                "type": "object",
+                "properties": {
+                    "type": { "const": "Empty" },
+                    "data": { "$ref": "#/$defs/Empty" },
+                },
+                "required": ["type", "data"],
+            },
+        ]);
+        assert_eq!(schema.get("oneOf"), Some(&expected));
+    }
+}
+
+mod field {
+    use super::Circle;
+    use schemars::{json_schema, schema_for, JsonSchema, Schema, SchemaGenerator};
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    #[typetag::serde(schema)]
+    trait Shape: Send {}
+
+    #[typetag::serde]
+    impl Shape for Circle {}
+
+    // Impls of a trait without `schema` need no JsonSchema impl.
+    #[typetag::serde]
+    trait Undescribed {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Plain;
+
+    #[typetag::serde]
+    impl Undescribed for Plain {}
+
+    struct Drawing;
+
+    impl JsonSchema for Drawing {
+        fn schema_name() -> Cow<'static, str> {
+            Cow::Borrowed("Drawing")
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            let shape = generator.subschema_for::<Box<dyn Shape>>();
+            let shapes = generator.subschema_for::<Vec<Box<dyn Shape + Send>>>();
+            json_schema!({
+                "type": "object",
+                "properties": {
+                    "background": shape,
+                    "shapes": shapes,
+                },
+            })
+        }
+    }
+
+    #[test]
+    fn test_definition() {
+        let schema = schema_for!(Drawing);
+        assert_eq!(
+            schema.get("properties"),
+            Some(&json!({
+                "background": { "$ref": "#/$defs/Shape" },
+                "shapes": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/Shape" },
+                },
+            })),
+        );
+        let definitions = schema.get("$defs").unwrap().as_object().unwrap();
+        assert_eq!(definitions.len(), 2);
+        assert!(definitions["Shape"].get("oneOf").is_some());
+        assert!(definitions["Circle"].get("properties").is_some());
+    }
+}
+
+#[test]
+fn test_impl_schema_is_shared() {
+    // The schema of the impl is generated through the generator it is given,
+    // so impls that refer to other definitions place them alongside.
+    #[typetag::serde(schema)]
+    trait Shape {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Group(Vec<Circle>);
+
+    impl JsonSchema for Group {
+        fn schema_name() -> Cow<'static, str> {
+            Cow::Borrowed("Group")
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            let circle = generator.subschema_for::<Circle>();
+            json_schema!({ "type": "array", "items": circle })
+        }
+    }
+
+    #[typetag::serde]
+    impl Shape for Group {}
+
+    let schema = schemars::schema_for!(Box<dyn Shape>);
+    assert_eq!(
+        schema.pointer("/$defs/Group/items"),
+        Some(&json!({ "$ref": "#/$defs/Circle" })),
+    );
+    assert!(schema.pointer("/$defs/Circle").is_some());
+}
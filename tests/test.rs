@@ -1,3 +1,4 @@
+#![cfg(not(feature = "runtime-registration"))]
 #![allow(clippy::extra_unused_type_parameters)]
 
 use serde::{Deserialize, Serialize};
@@ -69,6 +70,11 @@ mod externally_tagged {
         let trait_object: Box<dyn Trait> = postcard::from_bytes(&bytes).unwrap();
         trait_object.assert_a_is_11();
     }
+
+    #[test]
+    fn test_registered_names() {
+        assert_eq!(typetag::registered_names::<dyn Trait>(), ["A", "B"]);
+    }
 }
 
 mod internally_tagged {
@@ -296,6 +302,401 @@ mod other_types {
     }
 }
 
+mod internal_with_value_key {
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde(tag = "type", value_key = "data")]
+    trait Trait {}
+
+    #[typetag::serde]
+    impl Trait for u8 {}
+
+    #[typetag::serde]
+    impl Trait for Vec<u8> {}
+
+    #[typetag::serde(name = "Tuple")]
+    impl Trait for (u8, u8) {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Struct {
+        value: u8,
+    }
+
+    #[typetag::serde]
+    impl Trait for Struct {}
+
+    #[test]
+    fn test_json_round_trip() {
+        for json in [
+            r#"{"type":"u8","data":1}"#,
+            r#"{"type":"Vec","data":[1,2]}"#,
+            r#"{"type":"Tuple","data":[1,2]}"#,
+            r#"{"type":"Struct","value":1}"#,
+        ] {
+            let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+            assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+        }
+    }
+
+    #[test]
+    fn test_json_default_key() {
+        let json = r#"{"type":"u8","value":1}"#;
+        let error = serde_json::from_str::<Box<dyn Trait>>(json).err().unwrap();
+        let expected = "unknown field `value`, expected `data` at line 1 column 20";
+        assert_eq!(error.to_string(), expected);
+    }
+}
+
+mod untagged {
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde(untagged)]
+    trait Trait {
+        fn which(&self) -> &'static str;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Point3 {
+        x: i32,
+        y: i32,
+        z: i32,
+    }
+
+    #[typetag::serde]
+    impl Trait for Point {
+        fn which(&self) -> &'static str {
+            "Point"
+        }
+    }
+
+    #[typetag::serde]
+    impl Trait for Point3 {
+        fn which(&self) -> &'static str {
+            "Point3"
+        }
+    }
+
+    #[typetag::serde]
+    impl Trait for String {
+        fn which(&self) -> &'static str {
+            "String"
+        }
+    }
+
+    #[test]
+    fn test_json_serialize() {
+        let trait_object = &Point { x: 1, y: 2 } as &dyn Trait;
+        let json = serde_json::to_string(trait_object).unwrap();
+        assert_eq!(json, r#"{"x":1,"y":2}"#);
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{"x":1,"y":2,"z":3}"#;
+        let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.which(), "Point3");
+
+        let json = r#""text""#;
+        let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.which(), "String");
+    }
+
+    #[test]
+    fn test_json_no_match() {
+        let json = "1";
+        let error = serde_json::from_str::<Box<dyn Trait>>(json).err().unwrap();
+        let expected = "data did not match any impl of dyn Trait; \
+                        Point: invalid type: integer `1`, expected struct Point; \
+                        Point3: invalid type: integer `1`, expected struct Point3; \
+                        String: invalid type: integer `1`, expected a string";
+        assert_eq!(error.to_string(), expected);
+    }
+}
+
+mod variant {
+    use super::{A, B};
+
+    #[typetag::serde(variant)]
+    trait Trait {
+        fn a(&self) -> u8;
+    }
+
+    #[typetag::serde]
+    impl Trait for A {
+        fn a(&self) -> u8 {
+            self.a
+        }
+    }
+
+    #[typetag::serde(id = 2)]
+    impl Trait for B {
+        fn a(&self) -> u8 {
+            panic!("is not A!");
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let trait_object = &A { a: 11 } as &dyn Trait;
+        let json = serde_json::to_string(trait_object).unwrap();
+        assert_eq!(json, r#"{"A":{"a":11}}"#);
+
+        let trait_object: Box<dyn Trait> = serde_json::from_str(&json).unwrap();
+        assert_eq!(trait_object.a(), 11);
+    }
+
+    #[test]
+    fn test_ron_round_trip() {
+        let trait_object = &A { a: 11 } as &dyn Trait;
+        let ron = ron::to_string(trait_object).unwrap();
+        assert_eq!(ron, "A((a:11))");
+
+        let trait_object: Box<dyn Trait> = ron::from_str(&ron).unwrap();
+        assert_eq!(trait_object.a(), 11);
+    }
+
+    #[test]
+    fn test_postcard_round_trip() {
+        // The variant index is the explicit id.
+        let trait_object = &B { b: 11 } as &dyn Trait;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        assert_eq!(bytes, [2, 11]);
+
+        let trait_object = &A { a: 11 } as &dyn Trait;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        let trait_object: Box<dyn Trait> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(trait_object.a(), 11);
+    }
+
+    #[test]
+    fn test_ron_unknown_variant() {
+        let error = ron::from_str::<Box<dyn Trait>>("C((c:11))").err().unwrap();
+        let expected = "1:2: Unexpected variant named `C`in enum `Trait`, \
+                        expected either `A` or `B` instead";
+        assert_eq!(error.to_string(), expected);
+    }
+}
+
+mod numeric_ids {
+    use super::{A, B};
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde(tag_kind = "id")]
+    trait External {
+        fn a(&self) -> u8;
+    }
+
+    #[typetag::serde(id = 1)]
+    impl External for A {
+        fn a(&self) -> u8 {
+            self.a
+        }
+    }
+
+    #[typetag::serde(id = 300)]
+    impl External for B {
+        fn a(&self) -> u8 {
+            panic!("is not A!");
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct D;
+
+    #[typetag::serde]
+    impl External for D {
+        fn a(&self) -> u8 {
+            panic!("is not A!");
+        }
+    }
+
+    #[typetag::serde(tag = "type", tag_kind = "id")]
+    trait Internal {
+        fn a(&self) -> u8;
+    }
+
+    #[typetag::serde(id = 7)]
+    impl Internal for A {
+        fn a(&self) -> u8 {
+            self.a
+        }
+    }
+
+    #[typetag::serde(tag = "type", content = "c", tag_kind = "id")]
+    trait Adjacent {
+        fn a(&self) -> u8;
+    }
+
+    #[typetag::serde(id = 7)]
+    impl Adjacent for A {
+        fn a(&self) -> u8 {
+            self.a
+        }
+    }
+
+    #[test]
+    fn test_json_uses_names() {
+        let trait_object = &A { a: 11 } as &dyn External;
+        let json = serde_json::to_string(trait_object).unwrap();
+        assert_eq!(json, r#"{"A":{"a":11}}"#);
+
+        let trait_object: Box<dyn External> = serde_json::from_str(&json).unwrap();
+        assert_eq!(trait_object.a(), 11);
+    }
+
+    #[test]
+    fn test_postcard_uses_ids() {
+        let trait_object = &A { a: 11 } as &dyn External;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        assert_eq!(bytes, [1, 1, 11]);
+
+        let trait_object: Box<dyn External> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(trait_object.a(), 11);
+    }
+
+    #[test]
+    fn test_postcard_hashed_id() {
+        // FNV-1a of "D" as a varint.
+        let trait_object = &D as &dyn External;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        assert_eq!(bytes, [1, 147, 228, 175, 136, 12]);
+
+        let trait_object: Box<dyn External> = postcard::from_bytes(&bytes).unwrap();
+        let json = serde_json::to_string(&trait_object).unwrap();
+        assert_eq!(json, r#"{"D":null}"#);
+    }
+
+    #[test]
+    fn test_postcard_unknown_id() {
+        let error = postcard::from_bytes::<Box<dyn External>>(&[1, 2, 11])
+            .err()
+            .unwrap();
+        assert_eq!(error, postcard::Error::SerdeDeCustom);
+    }
+
+    #[test]
+    fn test_postcard_internally_tagged() {
+        let trait_object = &A { a: 11 } as &dyn Internal;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        let trait_object: Box<dyn Internal> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(trait_object.a(), 11);
+    }
+
+    #[test]
+    fn test_postcard_adjacently_tagged() {
+        let trait_object = &A { a: 11 } as &dyn Adjacent;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        let trait_object: Box<dyn Adjacent> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(trait_object.a(), 11);
+    }
+}
+
+mod unknown_tags {
+    use super::A;
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde(tag = "type", unknown = "Unknown")]
+    trait Internal {
+        fn tag(&self) -> Option<&str>;
+    }
+
+    #[typetag::serde]
+    impl Internal for A {
+        fn tag(&self) -> Option<&str> {
+            None
+        }
+    }
+
+    #[typetag::serde(name = "Unknown")]
+    impl Internal for typetag::Unknown {
+        fn tag(&self) -> Option<&str> {
+            typetag::Unknown::tag(self)
+        }
+    }
+
+    #[typetag::serde(tag = "type", content = "content", unknown = "Unknown")]
+    trait Adjacent {}
+
+    #[typetag::serde]
+    impl Adjacent for A {}
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Unknown(typetag::Unknown);
+
+    #[typetag::serde]
+    impl Adjacent for Unknown {}
+
+    #[typetag::serde(unknown = "Unknown")]
+    trait External {}
+
+    #[typetag::serde]
+    impl External for A {}
+
+    #[typetag::serde]
+    impl External for Unknown {}
+
+    #[test]
+    fn test_internally_tagged() {
+        let json = r#"{"a":1,"type":"Newer","nested":{"x":[1.5,null]}}"#;
+        let trait_object: Box<dyn Internal> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.tag(), Some("Newer"));
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+
+        let json = r#"{"type":"A","a":11}"#;
+        let trait_object: Box<dyn Internal> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.tag(), None);
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+    }
+
+    #[test]
+    fn test_adjacently_tagged() {
+        let json = r#"{"type":"Newer","content":[1,"two"]}"#;
+        let trait_object: Box<dyn Adjacent> = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+    }
+
+    #[test]
+    fn test_externally_tagged() {
+        let json = r#"{"Newer":{"b":true}}"#;
+        let trait_object: Box<dyn External> = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+
+        let json = r#"{"A":{"a":11}}"#;
+        let trait_object: Box<dyn External> = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+    }
+
+    #[test]
+    fn test_reserved_tag() {
+        let json = r#"{"type":"Unknown","a":1}"#;
+        let trait_object: Box<dyn Internal> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.tag(), Some("Unknown"));
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+
+        let json = r#"{"Unknown":{"a":1}}"#;
+        let trait_object: Box<dyn External> = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+    }
+
+    #[test]
+    fn test_errors_of_known_tags() {
+        let json = r#"{"type":"A"}"#;
+        let error = serde_json::from_str::<Box<dyn Internal>>(json)
+            .err()
+            .unwrap();
+        assert_eq!(error.to_string(), "missing field `a`");
+    }
+}
+
 mod internal_with_default {
     use super::{A, B};
 
@@ -410,60 +811,693 @@ mod adjacent_deny_unknown {
     }
 }
 
-mod marker_traits {
-    use serde::de::DeserializeOwned;
-    use serde::Serialize;
+mod aliases {
+    use super::{A, B, C};
 
-    #[typetag::serde]
-    trait Neither {}
+    #[typetag::serde(tag = "type")]
+    trait Trait {}
 
-    #[typetag::serde]
-    trait Sendable: Send {}
+    #[typetag::serde(name = "Renamed", alias = "A", alias = "Original")]
+    impl Trait for A {}
 
-    #[typetag::serde]
-    trait Syncable: Sync {}
+    #[typetag::serde(alias = "Original")]
+    impl Trait for B {}
 
     #[typetag::serde]
-    trait Both: Send + Sync {}
+    impl Trait for C {}
 
-    fn assert_serialize<T>()
-    where
-        T: ?Sized + Serialize,
-    {
+    #[test]
+    fn test_json_deserialize_alias() {
+        let json = r#"{"type":"A","a":11}"#;
+        let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+        let json = serde_json::to_string(&trait_object).unwrap();
+        let expected = r#"{"type":"Renamed","a":11}"#;
+        assert_eq!(json, expected);
     }
 
-    fn assert_deserialize<T>()
-    where
-        T: ?Sized,
-        Box<T>: DeserializeOwned,
-    {
+    #[test]
+    fn test_alias_collision() {
+        let json = r#"{"type":"Original","a":11}"#;
+        let error = serde_json::from_str::<Box<dyn Trait>>(json).err().unwrap();
+        let expected = r#"non-unique tag of dyn Trait: "Original" at line 1 column 18"#;
+        assert_eq!(error.to_string(), expected);
     }
 
     #[test]
-    fn test_serialize() {
-        assert_serialize::<dyn Neither>();
-        assert_serialize::<dyn Neither + Send>();
-        assert_serialize::<dyn Neither + Sync>();
-        assert_serialize::<dyn Neither + Send + Sync>();
+    fn test_registered_names() {
+        let names = typetag::registered_names::<dyn Trait>();
+        assert_eq!(names, ["B", "C", "Renamed"]);
+    }
+}
 
-        assert_serialize::<dyn Sendable>();
-        assert_serialize::<dyn Sendable + Send>();
-        assert_serialize::<dyn Sendable + Sync>();
-        assert_serialize::<dyn Sendable + Send + Sync>();
+mod validate {
+    use super::{A, B};
 
-        assert_serialize::<dyn Syncable>();
-        assert_serialize::<dyn Syncable + Send>();
-        assert_serialize::<dyn Syncable + Sync>();
-        assert_serialize::<dyn Syncable + Send + Sync>();
+    #[typetag::serde]
+    trait Trait {}
 
-        assert_serialize::<dyn Both>();
-        assert_serialize::<dyn Both + Send>();
-        assert_serialize::<dyn Both + Sync>();
-        assert_serialize::<dyn Both + Send + Sync>();
-    }
+    #[typetag::serde]
+    impl Trait for A {}
 
-    #[test]
-    fn test_deserialize() {
+    #[typetag::serde(name = "A")]
+    impl Trait for B {}
+
+    mod other {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize)]
+        pub struct C;
+
+        #[typetag::serde(name = "C", alias = "A")]
+        impl super::Trait for C {}
+    }
+
+    #[typetag::serde]
+    trait Unique {}
+
+    #[typetag::serde]
+    impl Unique for A {}
+
+    #[typetag::serde]
+    impl Unique for B {}
+
+    #[test]
+    fn test_validate() {
+        typetag::validate::<dyn Unique>().unwrap();
+
+        let error = typetag::validate::<dyn Trait>().unwrap_err();
+        let duplicates = error.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].tag(), "A");
+
+        let mut impls: Vec<_> = duplicates[0]
+            .impls()
+            .iter()
+            .map(|info| (info.type_name(), info.module_path()))
+            .collect();
+        impls.sort_unstable();
+        let expected = [
+            ("test::A", "test::validate"),
+            ("test::B", "test::validate"),
+            ("test::validate::other::C", "test::validate::other"),
+        ];
+        assert_eq!(impls, expected);
+    }
+
+    #[typetag::serde(tag_kind = "id")]
+    trait Numbered {}
+
+    #[typetag::serde(id = 5)]
+    impl Numbered for A {}
+
+    #[typetag::serde(id = 5)]
+    impl Numbered for B {}
+
+    #[test]
+    fn test_validate_ids() {
+        let error = typetag::validate::<dyn Numbered>().unwrap_err();
+        assert!(error.duplicates().is_empty());
+        let duplicate_ids = error.duplicate_ids();
+        assert_eq!(duplicate_ids.len(), 1);
+        assert_eq!(duplicate_ids[0].id(), 5);
+        assert_eq!(duplicate_ids[0].impls().len(), 2);
+
+        let error = postcard::from_bytes::<Box<dyn Numbered>>(&[1, 5, 0])
+            .err()
+            .unwrap();
+        assert_eq!(error, postcard::Error::SerdeDeCustom);
+    }
+}
+
+mod overrides {
+    use super::{A, B, C};
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde(tag = "type")]
+    trait Trait {
+        fn which(&self) -> &'static str;
+    }
+
+    #[typetag::serde]
+    impl Trait for A {
+        fn which(&self) -> &'static str {
+            "A"
+        }
+    }
+
+    #[typetag::serde(name = "A", override)]
+    impl Trait for B {
+        fn which(&self) -> &'static str {
+            "B"
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct D {
+        c: u8,
+    }
+
+    #[typetag::serde(name = "C", override)]
+    impl Trait for D {
+        fn which(&self) -> &'static str {
+            "D"
+        }
+    }
+
+    #[typetag::serde(override)]
+    impl Trait for C {
+        fn which(&self) -> &'static str {
+            "C"
+        }
+    }
+
+    #[test]
+    fn test_override() {
+        let json = r#"{"type":"A","b":11}"#;
+        let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.which(), "B");
+    }
+
+    #[test]
+    fn test_override_collision() {
+        let json = r#"{"type":"C","c":11}"#;
+        let error = serde_json::from_str::<Box<dyn Trait>>(json).err().unwrap();
+        let expected = r#"non-unique tag of dyn Trait: "C" at line 1 column 11"#;
+        assert_eq!(error.to_string(), expected);
+
+        let error = typetag::validate::<dyn Trait>().unwrap_err();
+        let duplicates = error.duplicates();
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].tag(), "C");
+    }
+}
+
+mod rename_all {
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde(tag = "type", rename_all = "snake_case")]
+    trait Trait {}
+
+    #[derive(Serialize, Deserialize)]
+    struct PriorFactor;
+
+    #[typetag::serde]
+    impl Trait for PriorFactor {}
+
+    #[derive(Serialize, Deserialize)]
+    struct BetweenFactor;
+
+    #[typetag::serde(name = "Between")]
+    impl Trait for BetweenFactor {}
+
+    #[typetag::serialize(rename_all = "SCREAMING-KEBAB-CASE")]
+    trait Generic<T> {}
+
+    #[typetag::serialize]
+    impl Generic<u8> for PriorFactor {}
+
+    #[test]
+    fn test_json_serialize() {
+        let trait_object = &PriorFactor as &dyn Trait;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"type":"prior_factor"}"#;
+        assert_eq!(json, expected);
+
+        let trait_object = &PriorFactor as &dyn Generic<u8>;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"PRIOR-FACTOR":null}"#;
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_registered_names() {
+        let names = typetag::registered_names::<dyn Trait>();
+        assert_eq!(names, ["Between", "prior_factor"]);
+    }
+
+    #[typetag::serialize(rename_all = "camelCase")]
+    trait Camel {}
+
+    #[typetag::serialize]
+    impl Camel for PriorFactor {}
+
+    #[derive(Serialize)]
+    struct Wrapper<T>(T);
+
+    #[typetag::serialize]
+    impl<T: Serialize> Camel for Wrapper<T> {}
+
+    #[typetag::serialize(rename_all = "kebab-case")]
+    trait Kebab {}
+
+    // Only ASCII letters change case, as in serde.
+    #[derive(Serialize)]
+    struct ÉtatÉlevé;
+
+    #[typetag::serialize]
+    impl Kebab for ÉtatÉlevé {}
+
+    #[test]
+    fn test_json_serialize_other_rules() {
+        let trait_object = &PriorFactor as &dyn Camel;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"priorFactor":null}"#;
+        assert_eq!(json, expected);
+
+        let trait_object = &Wrapper(1) as &dyn Camel;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"wrapper":1}"#;
+        assert_eq!(json, expected);
+
+        let trait_object = &ÉtatÉlevé as &dyn Kebab;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"État-Élevé":null}"#;
+        assert_eq!(json, expected);
+    }
+}
+
+mod name_strategy {
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde(name_strategy = "path")]
+    trait Trait {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Click;
+
+    #[typetag::serde]
+    impl Trait for Click {}
+
+    mod other {
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Serialize, Deserialize)]
+        pub struct Click;
+
+        #[typetag::serde]
+        impl super::Trait for Click {}
+
+        #[derive(Serialize, Deserialize)]
+        pub struct Named;
+
+        #[typetag::serde(name = "Named")]
+        impl super::Trait for Named {}
+    }
+
+    #[typetag::serialize(name_strategy = "path", rename_all = "kebab-case")]
+    trait Renamed {}
+
+    #[typetag::serialize]
+    impl Renamed for Click {}
+
+    #[test]
+    fn test_json_serialize() {
+        let trait_object = &Click as &dyn Trait;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"test::name_strategy::Click":null}"#;
+        assert_eq!(json, expected);
+
+        let trait_object = &Click as &dyn Renamed;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"test::name_strategy::click":null}"#;
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{"test::name_strategy::other::Click":null}"#;
+        let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+    }
+
+    #[test]
+    fn test_registered_names() {
+        let names = typetag::registered_names::<dyn Trait>();
+        let expected = [
+            "Named",
+            "test::name_strategy::Click",
+            "test::name_strategy::other::Click",
+        ];
+        assert_eq!(names, expected);
+    }
+}
+
+mod smart_pointers {
+    use super::{A, B};
+    use serde::Deserialize;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    #[typetag::serde(tag = "type")]
+    trait Trait: Send + Sync {
+        fn a(&self) -> u8;
+    }
+
+    #[typetag::serde]
+    impl Trait for A {
+        fn a(&self) -> u8 {
+            self.a
+        }
+    }
+
+    #[typetag::serde]
+    impl Trait for B {
+        fn a(&self) -> u8 {
+            panic!("is not A!");
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Graph {
+        #[serde(deserialize_with = "typetag::deserialize_arc")]
+        both: Arc<dyn Trait + Send + Sync>,
+        #[serde(deserialize_with = "typetag::deserialize_arc")]
+        send: Arc<dyn Trait + Send>,
+        #[serde(deserialize_with = "typetag::deserialize_rc")]
+        neither: Rc<dyn Trait>,
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{
+            "both": {"type": "A", "a": 1},
+            "send": {"type": "A", "a": 2},
+            "neither": {"type": "A", "a": 3}
+        }"#;
+        let graph: Graph = serde_json::from_str(json).unwrap();
+        assert_eq!(graph.both.a(), 1);
+        assert_eq!(graph.send.a(), 2);
+        assert_eq!(graph.neither.a(), 3);
+        let json = serde_json::to_string(&*graph.both).unwrap();
+        assert_eq!(json, r#"{"type":"A","a":1}"#);
+    }
+
+    #[test]
+    fn test_unknown_tag() {
+        let json = r#"{"type":"C","c":1}"#;
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let error = typetag::deserialize_rc::<_, dyn Trait>(&mut deserializer)
+            .err()
+            .unwrap();
+        let expected = "unknown variant `C`, expected `A` or `B` at line 1 column 11";
+        assert_eq!(error.to_string(), expected);
+    }
+}
+
+mod seed {
+    use serde::de::{DeserializeSeed, Deserializer};
+    use serde::{Deserialize, Serialize};
+    use typetag::{DeserializeWith, Seed};
+
+    struct Symbols {
+        names: Vec<&'static str>,
+    }
+
+    #[typetag::serde(tag = "type", seed = Symbols)]
+    trait Trait: Send + Sync {
+        fn name(&self) -> &'static str;
+    }
+
+    #[derive(Serialize)]
+    struct Symbol {
+        index: usize,
+        #[serde(skip)]
+        name: &'static str,
+    }
+
+    struct SymbolSeed<'a>(&'a Symbols);
+
+    impl<'de, 'a> DeserializeSeed<'de> for SymbolSeed<'a> {
+        type Value = Symbol;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Symbol, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            struct Index {
+                index: usize,
+            }
+
+            let Index { index } = Index::deserialize(deserializer)?;
+            match self.0.names.get(index) {
+                Some(name) => Ok(Symbol { index, name }),
+                None => Err(serde::de::Error::custom("symbol out of range")),
+            }
+        }
+    }
+
+    impl<'a> DeserializeWith<'a, Symbols> for Symbol {
+        type Seed = SymbolSeed<'a>;
+
+        fn seed(symbols: &'a Symbols) -> SymbolSeed<'a> {
+            SymbolSeed(symbols)
+        }
+    }
+
+    #[typetag::serde]
+    impl Trait for Symbol {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let symbols = Symbols {
+            names: vec!["x", "y"],
+        };
+        let json = r#"{"type":"Symbol","index":1}"#;
+
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let trait_object = Seed::<dyn Trait>::new(&symbols)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(trait_object.name(), "y");
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let trait_object = Seed::<dyn Trait + Send>::new(&symbols)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(trait_object.name(), "y");
+    }
+
+    #[test]
+    fn test_seed_error() {
+        let symbols = Symbols { names: vec!["x"] };
+        let json = r#"{"type":"Symbol","index":1}"#;
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let error = Seed::<dyn Trait>::new(&symbols)
+            .deserialize(&mut deserializer)
+            .err()
+            .unwrap();
+        let expected = "symbol out of range at line 1 column 27";
+        assert_eq!(error.to_string(), expected);
+    }
+
+    struct Interner<'a> {
+        words: &'a [String],
+    }
+
+    #[typetag::serde(seed = Interner<'_>)]
+    trait Phrase {
+        fn text(&self) -> String;
+    }
+
+    #[derive(Serialize)]
+    struct Word {
+        index: usize,
+        #[serde(skip)]
+        text: String,
+    }
+
+    struct WordSeed<'a>(&'a Interner<'a>);
+
+    impl<'de, 'a> DeserializeSeed<'de> for WordSeed<'a> {
+        type Value = Word;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Word, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            #[derive(Deserialize)]
+            struct Index {
+                index: usize,
+            }
+
+            let Index { index } = Index::deserialize(deserializer)?;
+            match self.0.words.get(index) {
+                Some(text) => Ok(Word {
+                    index,
+                    text: text.clone(),
+                }),
+                None => Err(serde::de::Error::custom("word out of range")),
+            }
+        }
+    }
+
+    impl<'a> DeserializeWith<'a, Interner<'a>> for Word {
+        type Seed = WordSeed<'a>;
+
+        fn seed(interner: &'a Interner<'a>) -> WordSeed<'a> {
+            WordSeed(interner)
+        }
+    }
+
+    #[typetag::serde]
+    impl Phrase for Word {
+        fn text(&self) -> String {
+            self.text.clone()
+        }
+    }
+
+    #[test]
+    fn test_borrowed_context() {
+        let words = vec!["hello".to_owned(), "world".to_owned()];
+        let interner = Interner { words: &words };
+        let json = r#"{"Word":{"index":1}}"#;
+
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let trait_object = Seed::<dyn Phrase>::new(&interner)
+            .deserialize(&mut deserializer)
+            .unwrap();
+        assert_eq!(trait_object.text(), "world");
+        drop(words);
+        assert_eq!(serde_json::to_string(&trait_object).unwrap(), json);
+    }
+}
+
+mod borrowed {
+    use serde::{Deserialize, Serialize};
+    use std::rc::Rc;
+
+    #[typetag::serde(tag = "type", borrow)]
+    trait Event<'de> {
+        fn message(&self) -> &'de str;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Line<'a> {
+        message: &'a str,
+    }
+
+    #[typetag::serde]
+    impl<'a> Event<'a> for Line<'a> {
+        fn message(&self) -> &'a str {
+            self.message
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Heartbeat;
+
+    #[typetag::serde]
+    impl<'a> Event<'a> for Heartbeat {
+        fn message(&self) -> &'a str {
+            ""
+        }
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{"type":"Line","message":"disk full"}"#;
+        let event: Box<dyn Event> = serde_json::from_str(json).unwrap();
+        let message = event.message();
+        assert_eq!(message, "disk full");
+        assert!(json.as_bytes().as_ptr_range().contains(&message.as_ptr()));
+        assert_eq!(serde_json::to_string(&event).unwrap(), json);
+    }
+
+    #[test]
+    fn test_json_deserialize_nested() {
+        let json = r#"[
+            {"type":"Heartbeat"},
+            {"message":"disk full","type":"Line"}
+        ]"#;
+        let events: Vec<Box<dyn Event>> = serde_json::from_str(json).unwrap();
+        assert_eq!(events.len(), 2);
+        let message = events[1].message();
+        assert_eq!(message, "disk full");
+        assert!(json.as_bytes().as_ptr_range().contains(&message.as_ptr()));
+    }
+
+    #[test]
+    fn test_json_deserialize_rc() {
+        let json = r#"{"type":"Line","message":"disk full"}"#;
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let event: Rc<dyn Event> = typetag::deserialize_rc(&mut deserializer).unwrap();
+        let message = event.message();
+        assert_eq!(message, "disk full");
+        assert!(json.as_bytes().as_ptr_range().contains(&message.as_ptr()));
+    }
+}
+
+mod marker_traits {
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use std::panic::UnwindSafe;
+
+    #[typetag::serde]
+    trait Neither {}
+
+    #[typetag::serde]
+    trait Sendable: Send {}
+
+    #[typetag::serde]
+    trait Syncable: Sync {}
+
+    #[typetag::serde]
+    trait Both: Send + Sync {}
+
+    #[typetag::serde]
+    trait Qualified: core::marker::Send + std::marker::Sync {}
+
+    #[typetag::serde]
+    trait Pinned: Unpin + std::panic::UnwindSafe + Send {}
+
+    fn assert_serialize<T>()
+    where
+        T: ?Sized + Serialize,
+    {
+    }
+
+    fn assert_deserialize<T>()
+    where
+        T: ?Sized,
+        Box<T>: DeserializeOwned,
+    {
+    }
+
+    #[test]
+    fn test_serialize() {
+        assert_serialize::<dyn Neither>();
+        assert_serialize::<dyn Neither + Send>();
+        assert_serialize::<dyn Neither + Sync>();
+        assert_serialize::<dyn Neither + Send + Sync>();
+
+        assert_serialize::<dyn Sendable>();
+        assert_serialize::<dyn Sendable + Send>();
+        assert_serialize::<dyn Sendable + Sync>();
+        assert_serialize::<dyn Sendable + Send + Sync>();
+
+        assert_serialize::<dyn Syncable>();
+        assert_serialize::<dyn Syncable + Send>();
+        assert_serialize::<dyn Syncable + Sync>();
+        assert_serialize::<dyn Syncable + Send + Sync>();
+
+        assert_serialize::<dyn Both>();
+        assert_serialize::<dyn Both + Send>();
+        assert_serialize::<dyn Both + Sync>();
+        assert_serialize::<dyn Both + Send + Sync>();
+
+        assert_serialize::<dyn Pinned + Sync>();
+        assert_serialize::<dyn Pinned + Unpin>();
+        assert_serialize::<dyn Pinned + Send + Sync + Unpin + UnwindSafe>();
+    }
+
+    #[test]
+    fn test_deserialize() {
         assert_deserialize::<dyn Neither>();
 
         assert_deserialize::<dyn Sendable>();
@@ -476,6 +1510,23 @@ mod marker_traits {
         assert_deserialize::<dyn Both + Send>();
         assert_deserialize::<dyn Both + Sync>();
         assert_deserialize::<dyn Both + Send + Sync>();
+
+        assert_deserialize::<dyn Qualified>();
+        assert_deserialize::<dyn Qualified + Send>();
+        assert_deserialize::<dyn Qualified + Send + Sync>();
+
+        assert_deserialize::<dyn Pinned>();
+        assert_deserialize::<dyn Pinned + Unpin>();
+        assert_deserialize::<dyn Pinned + UnwindSafe + Send>();
+        assert_deserialize::<dyn Pinned + Send + Unpin + UnwindSafe>();
+    }
+
+    #[test]
+    fn test_registered_names() {
+        assert!(typetag::registered_names::<dyn Neither>().is_empty());
+        assert!(typetag::registered_names::<dyn Sendable + Send>().is_empty());
+        assert!(typetag::registered_names::<dyn Both + Sync>().is_empty());
+        assert!(typetag::registered_names::<dyn Both + Send + Sync>().is_empty());
     }
 }
 
@@ -484,14 +1535,310 @@ mod generic {
     trait Generic<T> {}
 }
 
+mod generic_instantiated {
+    use super::{A, B};
+
+    #[typetag::serde(tag = "type", instantiate(Generic<f64>, Generic<f32>))]
+    trait Generic<T> {
+        fn value(&self) -> T;
+    }
+
+    #[typetag::serde]
+    impl Generic<f64> for A {
+        fn value(&self) -> f64 {
+            f64::from(self.a)
+        }
+    }
+
+    #[typetag::serde]
+    impl Generic<f32> for A {
+        fn value(&self) -> f32 {
+            f32::from(self.a) / 2.0
+        }
+    }
+
+    #[typetag::serde]
+    impl Generic<f64> for B {
+        fn value(&self) -> f64 {
+            -f64::from(self.b)
+        }
+    }
+
+    // Not a listed instantiation, so registered nowhere.
+    #[typetag::serde]
+    impl Generic<i32> for B {
+        fn value(&self) -> i32 {
+            -i32::from(self.b)
+        }
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{"type":"A","a":11}"#;
+        let trait_object: Box<dyn Generic<f64>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.value(), 11.0);
+        let trait_object: Box<dyn Generic<f32>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.value(), 5.5);
+
+        let json = r#"{"type":"B","b":11}"#;
+        let trait_object: Box<dyn Generic<f64>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.value(), -11.0);
+    }
+
+    #[test]
+    fn test_json_deserialize_separate_registries() {
+        let json = r#"{"type":"B","b":11}"#;
+        let error = serde_json::from_str::<Box<dyn Generic<f32>>>(json)
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            error,
+            "unknown variant `B`, expected `A` at line 1 column 11",
+        );
+    }
+
+    #[test]
+    fn test_registered_names() {
+        assert_eq!(typetag::registered_names::<dyn Generic<f64>>(), ["A", "B"]);
+        assert_eq!(typetag::registered_names::<dyn Generic<f32>>(), ["A"]);
+    }
+
+    #[test]
+    fn test_unlisted_instantiation() {
+        let trait_object = &B { b: 11 } as &dyn Generic<i32>;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"type":"B","b":11}"#;
+        assert_eq!(json, expected);
+        assert_eq!(trait_object.value(), -11);
+    }
+
+    #[test]
+    fn test_postcard_round_trip() {
+        let trait_object = &B { b: 11 } as &dyn Generic<f64>;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        let trait_object: Box<dyn Generic<f64>> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(trait_object.value(), -11.0);
+    }
+}
+
+mod generic_impl {
+    use serde::{Deserialize, Serialize};
+
+    #[typetag::serde]
+    trait Trait {
+        fn width(&self) -> usize;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Between<T> {
+        delta: T,
+    }
+
+    #[typetag::serde(instantiate(Between<u8> = "Between8", Between<u16> = "Between16"))]
+    impl<T: Serialize> Trait for Between<T> {
+        fn width(&self) -> usize {
+            std::mem::size_of::<T>()
+        }
+    }
+
+    #[typetag::serde(tag = "type", instantiate(Generic<f64>, Generic<f32>))]
+    trait Generic<T> {
+        fn value(&self) -> T;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Constant<T> {
+        value: T,
+    }
+
+    #[typetag::serde(instantiate(Constant<f64> = "ConstantF64", Constant<f32> = "ConstantF32"))]
+    impl<T: Serialize + Copy> Generic<T> for Constant<T> {
+        fn value(&self) -> T {
+            self.value
+        }
+    }
+
+    #[test]
+    fn test_json_serialize() {
+        let trait_object = &Between { delta: 1u8 } as &dyn Trait;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"Between8":{"delta":1}}"#;
+        assert_eq!(json, expected);
+
+        let trait_object = &Between { delta: 1u16 } as &dyn Trait;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"Between16":{"delta":1}}"#;
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{"Between16":{"delta":1}}"#;
+        let trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.width(), 2);
+    }
+
+    #[test]
+    fn test_json_deserialize_generic_trait() {
+        let json = r#"{"type":"ConstantF32","value":1.5}"#;
+        let trait_object: Box<dyn Generic<f32>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.value(), 1.5);
+
+        let json = r#"{"type":"ConstantF64","value":2.5}"#;
+        let trait_object: Box<dyn Generic<f64>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.value(), 2.5);
+    }
+
+    #[test]
+    fn test_unlisted_instantiation() {
+        // The impl stays as generic as written, with only the listed
+        // instantiations registered.
+        let between = Between { delta: -1i32 };
+        assert_eq!(between.width(), 4);
+
+        let error = serde_json::to_string(&between as &dyn Trait).unwrap_err();
+        let expected = "unlisted instantiation of a generic impl of dyn Trait";
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn test_postcard_round_trip() {
+        let trait_object = &Between { delta: 300u16 } as &dyn Trait;
+        let bytes = postcard::to_stdvec(trait_object).unwrap();
+        let trait_object: Box<dyn Trait> = postcard::from_bytes(&bytes).unwrap();
+        assert_eq!(trait_object.width(), 2);
+    }
+}
+
 #[rustversion::since(1.74)]
 mod assoc_type {
+    use super::A;
+
     #[typetag::serde]
     trait Trait {
         type AssocType
         where
             Self: Sized;
     }
+
+    #[typetag::serde]
+    impl Trait for A {
+        type AssocType = u8;
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{"A":{"a":11}}"#;
+        let _trait_object: Box<dyn Trait> = serde_json::from_str(json).unwrap();
+    }
+}
+
+mod assoc_type_instantiated {
+    use super::{A, B};
+    use serde::{Deserialize, Serialize};
+    use std::fmt::Debug;
+
+    #[typetag::serde(instantiate(Model<Output = f64>, Model<Output = String>))]
+    trait Model {
+        type Output: Debug;
+        fn eval(&self) -> Self::Output;
+    }
+
+    #[typetag::serde]
+    impl Model for A {
+        type Output = f64;
+        fn eval(&self) -> f64 {
+            f64::from(self.a)
+        }
+    }
+
+    #[typetag::serde]
+    impl Model for B {
+        type Output = String;
+        fn eval(&self) -> String {
+            self.b.to_string()
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Count(u32);
+
+    // Binds an associated type that is not listed, so registered nowhere.
+    #[typetag::serde]
+    impl Model for Count {
+        type Output = u32;
+        fn eval(&self) -> u32 {
+            self.0
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Constant<T> {
+        value: T,
+    }
+
+    #[typetag::serde(instantiate(Constant<f64> = "ConstantF64"))]
+    impl<T: Serialize + Debug + Clone> Model for Constant<T> {
+        type Output = T;
+        fn eval(&self) -> T {
+            self.value.clone()
+        }
+    }
+
+    #[test]
+    fn test_json_serialize() {
+        let trait_object = &B { b: 11 } as &dyn Model<Output = String>;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"B":{"b":11}}"#;
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_json_deserialize() {
+        let json = r#"{"A":{"a":11}}"#;
+        let trait_object: Box<dyn Model<Output = f64>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.eval(), 11.0);
+
+        let json = r#"{"ConstantF64":{"value":1.5}}"#;
+        let trait_object: Box<dyn Model<Output = f64>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.eval(), 1.5);
+
+        let json = r#"{"B":{"b":11}}"#;
+        let trait_object: Box<dyn Model<Output = String>> = serde_json::from_str(json).unwrap();
+        assert_eq!(trait_object.eval(), "11");
+    }
+
+    #[test]
+    fn test_json_deserialize_separate_registries() {
+        let json = r#"{"A":{"a":11}}"#;
+        let error = serde_json::from_str::<Box<dyn Model<Output = String>>>(json)
+            .err()
+            .unwrap()
+            .to_string();
+        assert_eq!(
+            error,
+            "unknown variant `A`, expected `B` at line 1 column 4"
+        );
+    }
+
+    #[test]
+    fn test_unlisted_binding() {
+        let trait_object = &Count(11) as &dyn Model<Output = u32>;
+        let json = serde_json::to_string(trait_object).unwrap();
+        let expected = r#"{"Count":11}"#;
+        assert_eq!(json, expected);
+        assert_eq!(trait_object.eval(), 11);
+
+        assert_eq!(
+            typetag::registered_names::<dyn Model<Output = f64>>(),
+            ["A", "ConstantF64"]
+        );
+        assert_eq!(
+            typetag::registered_names::<dyn Model<Output = String>>(),
+            ["B"]
+        );
+    }
 }
 
 mod macro_expanded {
@@ -511,6 +1858,289 @@ mod macro_expanded {
 }
 
 // https://github.com/dtolnay/typetag/issues/28
+mod clone_box {
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[typetag::serde(tag = "type")]
+    trait Trait: Send {
+        fn describe(&self) -> String;
+    }
+
+    impl Clone for Box<dyn Trait> {
+        fn clone(&self) -> Self {
+            typetag::clone_box(&**self)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    enum Shape {
+        Point,
+        Circle(f64),
+        Segment(i32, i32),
+        Rect { w: u32, h: i8 },
+    }
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Unit;
+
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Meters(f32);
+
+    #[derive(Serialize, Deserialize)]
+    struct Scene {
+        shapes: Vec<Shape>,
+        unit: Unit,
+        scale: Meters,
+        label: Option<char>,
+        counts: BTreeMap<String, (u8, bool)>,
+        child: Option<Box<dyn Trait>>,
+    }
+
+    #[typetag::serde]
+    impl Trait for Scene {
+        fn describe(&self) -> String {
+            let child = self.child.as_ref().map(|child| child.describe());
+            format!(
+                "{:?} {:?} {:?} {:?} {:?} {:?}",
+                self.shapes, self.unit, self.scale, self.label, self.counts, child,
+            )
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Number(u64);
+
+    #[typetag::serde]
+    impl Trait for Number {
+        fn describe(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    #[typetag::serde(variant)]
+    trait Indexed {
+        fn value(&self) -> u16;
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Value(u16);
+
+    #[typetag::serde(id = 7)]
+    impl Indexed for Value {
+        fn value(&self) -> u16 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_clone_box() {
+        let mut counts = BTreeMap::new();
+        counts.insert("a".to_owned(), (1, true));
+        let scene: Box<dyn Trait> = Box::new(Scene {
+            shapes: vec![
+                Shape::Point,
+                Shape::Circle(1.5),
+                Shape::Segment(-1, 2),
+                Shape::Rect { w: 3, h: -4 },
+            ],
+            unit: Unit,
+            scale: Meters(0.5),
+            label: Some('x'),
+            counts,
+            child: Some(Box::new(Number(9))),
+        });
+
+        let clone = scene.clone();
+        let expected = "[Point, Circle(1.5), Segment(-1, 2), Rect { w: 3, h: -4 }] Unit \
+                        Meters(0.5) Some('x') {\"a\": (1, true)} Some(\"9\")";
+        assert_eq!(clone.describe(), expected);
+        assert_eq!(scene.describe(), expected);
+    }
+
+    #[test]
+    fn test_clone_box_marker() {
+        let number: Box<dyn Trait + Send> = Box::new(Number(1));
+        let clone = typetag::clone_box(&*number);
+        assert_eq!(clone.describe(), "1");
+    }
+
+    #[test]
+    fn test_clone_box_variant() {
+        let value: Box<dyn Indexed> = Box::new(Value(3));
+        assert_eq!(typetag::clone_box(&*value).value(), 3);
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Wide(u128);
+
+    #[typetag::serde]
+    impl Trait for Wide {
+        fn describe(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "u128 is not supported")]
+    fn test_clone_box_u128() {
+        let wide: Box<dyn Trait> = Box::new(Wide(u128::MAX));
+        let _ = wide.clone();
+    }
+}
+
+mod derive {
+    use serde::{Deserialize, Serialize};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{HashMap, HashSet};
+    use std::hash::{Hash, Hasher};
+
+    #[typetag::serde(tag = "type", derive(Debug, PartialEq, Eq, Hash))]
+    trait Event: Send {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Click {
+        x: i32,
+        y: i32,
+    }
+
+    #[typetag::serde]
+    impl Event for Click {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Tap {
+        x: i32,
+        y: i32,
+    }
+
+    #[typetag::serde]
+    impl Event for Tap {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Scroll(f64);
+
+    #[typetag::serde]
+    impl Event for Scroll {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Blur;
+
+    #[typetag::serde]
+    impl Event for Blur {}
+
+    #[derive(Serialize, Deserialize)]
+    struct Keys {
+        pressed: HashMap<String, Option<char>>,
+        modifiers: (bool, Vec<u8>),
+    }
+
+    #[typetag::serde(name = "keys")]
+    impl Event for Keys {}
+
+    fn hash(event: &dyn Event) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        event.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[test]
+    fn test_debug() {
+        let click: Box<dyn Event> = Box::new(Click { x: 10, y: 10 });
+        assert_eq!(format!("{:?}", click), "Click { x: 10, y: 10 }");
+        assert_eq!(
+            format!("{:?}", Box::new(Scroll(1.5)) as Box<dyn Event>),
+            "Scroll(1.5)"
+        );
+        assert_eq!(format!("{:?}", &Blur as &dyn Event), "Blur");
+
+        let mut pressed = HashMap::new();
+        pressed.insert("b".to_owned(), None);
+        pressed.insert("a".to_owned(), Some('x'));
+        let keys = Keys {
+            pressed,
+            modifiers: (true, vec![1]),
+        };
+        let expected = r#"keys { pressed: {"a": Some('x'), "b": None}, modifiers: (true, [1]) }"#;
+        assert_eq!(format!("{:?}", &keys as &(dyn Event + Send)), expected);
+    }
+
+    #[test]
+    fn test_eq() {
+        let click = &Click { x: 10, y: 10 } as &dyn Event;
+        assert!(*click == *(&Click { x: 10, y: 10 } as &dyn Event));
+        assert!(*click != *(&Click { x: 10, y: 11 } as &dyn Event));
+        assert!(*click != *(&Tap { x: 10, y: 10 } as &dyn Event));
+
+        let boxed: Box<dyn Event + Send> = Box::new(Scroll(0.0));
+        assert_eq!(&*boxed, &Scroll(-0.0) as &(dyn Event + Send));
+        assert_eq!(
+            &Scroll(f64::NAN) as &dyn Event,
+            &Scroll(-f64::NAN) as &dyn Event,
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut first = HashMap::new();
+        let mut second = HashMap::new();
+        for i in 0..10 {
+            first.insert(i.to_string(), Some('a'));
+        }
+        for i in (0..10).rev() {
+            second.insert(i.to_string(), Some('a'));
+        }
+        let first = Keys {
+            pressed: first,
+            modifiers: (false, Vec::new()),
+        };
+        let second = Keys {
+            pressed: second,
+            modifiers: (false, Vec::new()),
+        };
+        assert_eq!(&first as &dyn Event, &second as &dyn Event);
+        assert_eq!(hash(&first), hash(&second));
+        assert_eq!(hash(&Scroll(0.0)), hash(&Scroll(-0.0)));
+
+        let mut set: HashSet<Box<dyn Event>> = HashSet::new();
+        set.insert(Box::new(Click { x: 1, y: 2 }));
+        set.insert(Box::new(Click { x: 1, y: 2 }));
+        set.insert(Box::new(Tap { x: 1, y: 2 }));
+        assert_eq!(set.len(), 2);
+    }
+
+    // Derives listed in any order.
+    #[typetag::serialize(derive(Hash, Eq, PartialEq))]
+    trait Unordered {}
+
+    struct Unserializable;
+
+    impl Serialize for Unserializable {
+        fn serialize<S>(&self, _serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: serde::Serializer,
+        {
+            Err(serde::ser::Error::custom("unserializable"))
+        }
+    }
+
+    #[typetag::serialize]
+    impl Unordered for Unserializable {}
+
+    #[test]
+    #[should_panic(expected = "failed to serialize Unserializable for comparison")]
+    fn test_eq_serialize_error() {
+        let trait_object = &Unserializable as &dyn Unordered;
+        let _ = *trait_object == *trait_object;
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to serialize Unserializable for comparison")]
+    fn test_hash_serialize_error() {
+        let mut hasher = DefaultHasher::new();
+        (&Unserializable as &dyn Unordered).hash(&mut hasher);
+    }
+}
+
 mod trait_hierarchy {
     use serde::{Deserialize, Serialize};
 
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+#[typetag::serde]
+trait Trait {}
+
+#[derive(Serialize, Deserialize)]
+struct Struct;
+
+#[typetag::serde(name = "Struct", alias = "Struct")]
+impl Trait for Struct {}
+
+#[derive(Serialize, Deserialize)]
+struct Other;
+
+#[typetag::serde(alias = "Other2", alias = "Other2")]
+impl Trait for Other {}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+#[typetag::serde]
+pub trait Trait {}
+
+#[derive(Serialize, Deserialize)]
+pub struct Struct;
+
+#[typetag::serde(id = 1)]
+impl Trait for Struct {}
+
+fn main() {}
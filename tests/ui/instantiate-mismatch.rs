@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+#[typetag::serde]
+pub trait Trait {}
+
+#[derive(Serialize, Deserialize)]
+pub struct Struct<T>(T);
+
+#[derive(Serialize, Deserialize)]
+pub struct Other<T>(T);
+
+#[typetag::serde(instantiate(Struct<u8> = "Struct8", Other<u16> = "Other16"))]
+impl<T: Serialize> Trait for Struct<T> {}
+
+fn main() {}
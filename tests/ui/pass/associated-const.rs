@@ -0,0 +1,26 @@
+#![feature(generic_const_items)]
+#![allow(incomplete_features)]
+
+use serde::{Deserialize, Serialize};
+
+#[typetag::serde]
+pub trait Trait {
+    const ASSOC: u8
+    where
+        Self: Sized;
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Struct;
+
+#[typetag::serde]
+impl Trait for Struct {
+    const ASSOC: u8 = 1;
+}
+
+fn main() {
+    assert_eq!(Struct::ASSOC, 1);
+    let trait_object: Box<dyn Trait> = serde_json::from_str(r#"{"Struct":null}"#).unwrap();
+    let json = serde_json::to_string(&trait_object).unwrap();
+    assert_eq!(json, r#"{"Struct":null}"#);
+}
@@ -0,0 +1,4 @@
+#[typetag::serde(tag = "type", unknown = "Other", schema)]
+trait Trait {}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[typetag::serde(rename_all = "Title Case")]
+trait Trait {}
+
+fn main() {}
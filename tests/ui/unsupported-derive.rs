@@ -0,0 +1,4 @@
+#[typetag::serde(derive(Debug, Clone))]
+trait Trait {}
+
+fn main() {}